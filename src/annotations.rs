@@ -0,0 +1,280 @@
+//! Storage of predicted footings inside `.sm`/`.ssc` files.
+//!
+//! The footing of a chart is written as a `#FOOTING` tag right after its `#NOTES` tag,
+//! with the foot placement of one row per line.
+//! StepMania (and other simfile parsers) ignore unknown tags, so the chart keeps working:
+//! ```text
+//! #NOTES:
+//!      dance-single:
+//!      ...
+//! ;
+//! #FOOTING:
+//! 0 L---
+//! 1 L--R
+//! ;
+//! ```
+use crate::feet::FootPlacement;
+use crate::graph::StepEntry;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+const FOOTING_TAG: &str = "FOOTING";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FootingError {
+    /// A footing line couldn't be parsed.
+    InvalidLine(String),
+    /// The footing rows are not listed in order, or a row is missing.
+    UnexpectedRow { expected: usize, actual: usize },
+}
+
+impl Display for FootingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FootingError::InvalidLine(line) => write!(f, "Invalid footing line `{line}`"),
+            FootingError::UnexpectedRow { expected, actual } => {
+                write!(f, "Expected footing for row {expected}, got row {actual}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FootingError {}
+
+/// A `#NAME:VALUE;` tag in a simfile.
+pub(crate) struct Tag<'a> {
+    pub(crate) name: &'a str,
+    /// Range of the whole tag, from `#` to `;` (included).
+    pub(crate) range: Range<usize>,
+    pub(crate) value: &'a str,
+}
+
+impl Tag<'_> {
+    pub(crate) fn is(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+
+    pub(crate) fn is_notes(&self) -> bool {
+        self.is("NOTES") || self.is("NOTES2")
+    }
+}
+
+pub(crate) fn tags(simfile: &str) -> Vec<Tag<'_>> {
+    let bytes = simfile.as_bytes();
+    let skip_comment = |i: usize| {
+        simfile[i..]
+            .find('\n')
+            .map_or(simfile.len(), |end| i + end + 1)
+    };
+
+    let mut tags = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'/' && bytes.get(i + 1) == Some(&b'/') {
+            i = skip_comment(i);
+            continue;
+        }
+        if bytes[i] != b'#' {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let name_end = simfile[i..].find(':').map_or(bytes.len(), |end| i + end);
+        let (value_end, end) = 'end: {
+            let mut j = name_end;
+            while j < bytes.len() {
+                match bytes[j] {
+                    b'/' if bytes.get(j + 1) == Some(&b'/') => j = skip_comment(j),
+                    b';' => break 'end (j, j + 1),
+                    // A tag without terminator, StepMania stops the value at the next tag.
+                    b'#' if bytes[j - 1] == b'\n' => break 'end (j, j),
+                    _ => j += 1,
+                }
+            }
+            (bytes.len(), bytes.len())
+        };
+
+        tags.push(Tag {
+            name: simfile[start + 1..name_end].trim(),
+            range: start..end,
+            value: &simfile[(name_end + 1).min(value_end)..value_end],
+        });
+        i = end.max(start + 1);
+    }
+
+    tags
+}
+
+/// Remove every footing tag from the simfile.
+pub fn strip_footings(simfile: &str) -> String {
+    let mut result = String::with_capacity(simfile.len());
+    let mut copied = 0;
+    let mut prev_end = 0;
+    for tag in tags(simfile) {
+        if tag.is(FOOTING_TAG) {
+            // Also remove the whitespace separating the tag from the previous one
+            let start = if simfile[prev_end..tag.range.start].trim().is_empty() {
+                prev_end
+            } else {
+                tag.range.start
+            };
+            result.push_str(&simfile[copied..start]);
+            copied = tag.range.end;
+        }
+        prev_end = tag.range.end;
+    }
+    result.push_str(&simfile[copied..]);
+    result
+}
+
+/// Write the footings right after their `#NOTES` tag.
+///
+/// `footings` is indexed in the same order as the `#NOTES` tags
+/// (i.e the same order as [`danceparser::SMChart::notes`]),
+/// existing footings are replaced, and charts with a `None` footing are left without one.
+pub fn write_footings(simfile: &str, footings: &[Option<&[StepEntry]>]) -> String {
+    let simfile = strip_footings(simfile);
+    let newline = if simfile.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+
+    let notes = tags(&simfile).into_iter().filter(Tag::is_notes);
+
+    let mut result = String::with_capacity(simfile.len());
+    let mut copied = 0;
+    for (tag, footing) in notes.zip(footings) {
+        let Some(steps) = footing else {
+            continue;
+        };
+
+        result.push_str(&simfile[copied..tag.range.end]);
+        copied = tag.range.end;
+
+        result.push_str(newline);
+        result.push_str(&format!("#{FOOTING_TAG}:"));
+        for step in *steps {
+            result.push_str(newline);
+            result.push_str(&format!("{} {}", step.row_index, step.columns));
+        }
+        result.push_str(newline);
+        result.push(';');
+    }
+    result.push_str(&simfile[copied..]);
+
+    result
+}
+
+fn parse_footing(value: &str) -> Result<Vec<FootPlacement>, FootingError> {
+    let mut placements = Vec::new();
+    for line in value.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let invalid = || FootingError::InvalidLine(line.to_owned());
+        let (row_index, columns) = line.split_once(' ').ok_or_else(invalid)?;
+        let row_index = row_index.parse::<usize>().map_err(|_| invalid())?;
+        let columns = FootPlacement::parse(columns.trim()).ok_or_else(invalid)?;
+
+        if row_index != placements.len() {
+            return Err(FootingError::UnexpectedRow {
+                expected: placements.len(),
+                actual: row_index,
+            });
+        }
+        placements.push(columns);
+    }
+    Ok(placements)
+}
+
+/// Read the footings written by [`write_footings`].
+///
+/// The result has one entry per `#NOTES` tag, containing the foot placement of every row.
+pub fn read_footings(simfile: &str) -> Result<Vec<Option<Vec<FootPlacement>>>, FootingError> {
+    let mut footings = Vec::new();
+    let mut after_notes = false;
+    for tag in tags(simfile) {
+        if tag.is_notes() {
+            footings.push(None);
+            after_notes = true;
+            continue;
+        }
+
+        if tag.is(FOOTING_TAG) && after_notes {
+            *footings.last_mut().unwrap() = Some(parse_footing(tag.value)?);
+        }
+        after_notes = false;
+    }
+    Ok(footings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_steps;
+    use danceparser::SMChart;
+    use danceparser::view::NoteViewer;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_footing_round_trip() {
+        let simfile = include_str!("../Eientewi Set 12A.sm");
+        let chart =
+            SMChart::from_sm(Cursor::new(simfile)).expect("Failed to parse StepMania chart");
+
+        let steps = chart
+            .notes
+            .iter()
+            .map(|diff| generate_steps(NoteViewer::new(&chart, diff)))
+            .collect::<Vec<_>>();
+        let mut footings = steps.iter().map(|s| Some(s.as_slice())).collect::<Vec<_>>();
+        footings[1] = None;
+
+        let written = write_footings(simfile, &footings);
+        let rewritten = SMChart::from_sm(Cursor::new(&written)).expect("Footings broke the chart");
+        assert_eq!(rewritten, chart);
+        // Writing again replaces the existing footings
+        assert_eq!(write_footings(&written, &footings), written);
+        assert_eq!(strip_footings(&written), simfile);
+
+        let read = read_footings(&written).unwrap();
+        assert_eq!(read.len(), steps.len());
+        assert_eq!(read[1], None);
+        for i in [0, 2, 3, 4] {
+            let expected = steps[i]
+                .iter()
+                .map(|s| s.columns.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(read[i].as_ref(), Some(&expected));
+        }
+    }
+
+    #[test]
+    fn test_read_ssc_footing() {
+        let simfile = "#TITLE:Test;\n\
+            #NOTEDATA:;\n\
+            #STEPSTYPE:dance-single;\n\
+            #NOTES:\n1000\n0001\n;\n\
+            #FOOTING:\n0 L---\n1 L--R\n;\n\
+            #NOTEDATA:;\n\
+            #STEPSTYPE:dance-single;\n\
+            #NOTES:\n1000\n;\n";
+
+        assert_eq!(
+            read_footings(simfile),
+            Ok(vec![
+                Some(vec![
+                    FootPlacement::parse("L---").unwrap(),
+                    FootPlacement::parse("L--R").unwrap(),
+                ]),
+                None,
+            ])
+        );
+        assert_eq!(
+            read_footings("#NOTES:\n1000\n;\n#FOOTING:\n1 L---\n;\n"),
+            Err(FootingError::UnexpectedRow {
+                expected: 0,
+                actual: 1
+            })
+        );
+    }
+}
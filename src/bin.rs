@@ -1,32 +1,824 @@
+use clap::{Parser, Subcommand, ValueEnum};
 use danceparser::{
-    SMChart,
+    NotesData, SMChart,
     view::{NoteView, NoteViewer},
 };
-use dancepredictor::{DanceStage, StepGraph};
-use petgraph::dot::Dot;
-use std::io::{Cursor, Write};
+use dancepredictor::{
+    Accuracy, AnnotatedChart, ChartStats, CostWeights, DanceStage, DifficultyEstimate, Error,
+    Evaluation, FitOptions, FootPlacement, Predictor, Profile, StepEntry, StepGraph, Tech, export,
+    fit_weights, parallel_map, read_footings, ssc_to_sm,
+};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Predict the footing of a single chart
+    Predict(PredictArgs),
+    /// Predict every supported difficulty of every chart in a directory
+    Batch(BatchArgs),
+    /// Compare the predicted footings to the footings annotated in the charts
+    Eval(EvalArgs),
+    /// Fit the cost weights to the footings annotated in the charts
+    Fit(FitArgs),
+}
+
+#[derive(clap::Args)]
+struct PredictArgs {
+    /// Path to the .sm or .ssc file
+    chart: PathBuf,
+    /// Difficulty to predict, either its index or its name (e.g "Challenge")
+    #[arg(short, long)]
+    difficulty: Option<String>,
+    /// Only consider difficulties of this style (e.g "dance-single")
+    #[arg(short, long)]
+    style: Option<String>,
+    #[command(flatten)]
+    model: ModelArgs,
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    /// Write the step graph in the DOT format to this path
+    #[arg(long)]
+    dot: Option<PathBuf>,
+    /// First row written to the DOT file
+    #[arg(long, default_value_t = 0, requires = "dot")]
+    dot_from: usize,
+    /// Row after the last row written to the DOT file, defaults to the end of the chart
+    #[arg(long, requires = "dot")]
+    dot_to: Option<usize>,
+    /// Time budget for the prediction in seconds, a faster but less accurate search is used past it
+    #[arg(long)]
+    time_budget: Option<f64>,
+}
+
+#[derive(clap::Args)]
+struct BatchArgs {
+    /// Directory to search for .sm and .ssc files, recursively
+    directory: PathBuf,
+    /// Directory to write the results and the report to
+    #[arg(short, long)]
+    output: PathBuf,
+    #[command(flatten)]
+    model: ModelArgs,
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Json)]
+    format: OutputFormat,
+    /// Amount of charts processed at the same time, defaults to the amount of CPU cores
+    #[arg(short, long, default_value_t = default_jobs())]
+    jobs: usize,
+    /// Time budget for each chart in seconds, a faster but less accurate search is used past it
+    #[arg(long)]
+    time_budget: Option<f64>,
+}
 
-fn main() {
-    let dance_stage = DanceStage::ddr_solo();
-    let mut graph = StepGraph::new(dance_stage);
+#[derive(clap::Args)]
+struct EvalArgs {
+    /// .sm and .ssc files with `#FOOTING` tags, or directories to search for them recursively
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    #[command(flatten)]
+    model: ModelArgs,
+    #[arg(short, long, value_enum, default_value_t = ReportFormat::Text)]
+    format: ReportFormat,
+    /// Amount of charts processed at the same time, defaults to the amount of CPU cores
+    #[arg(short, long, default_value_t = default_jobs())]
+    jobs: usize,
+}
 
-    let chart = SMChart::from_sm(Cursor::new(include_str!("../basic.sm")))
-        .expect("Failed to parse StepMania chart");
-    let notes_data = chart.notes.first().unwrap();
+#[derive(clap::Args)]
+struct FitArgs {
+    /// .sm and .ssc files with `#FOOTING` tags, or directories to search for them recursively
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    #[command(flatten)]
+    model: ModelArgs,
+    /// Write the fitted weights as JSON to this path
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Maximum amount of passes over every weight
+    #[arg(long, default_value_t = FitOptions::default().max_rounds)]
+    rounds: usize,
+    /// Amount of charts processed at the same time, defaults to the amount of CPU cores
+    #[arg(short, long, default_value_t = default_jobs())]
+    jobs: usize,
+}
 
-    for NoteView { time, row, .. } in NoteViewer::new(&chart, notes_data) {
-        graph.append(time, row);
+/// Settings of the predictor, shared by every command.
+#[derive(clap::Args)]
+struct ModelArgs {
+    /// Stage layout, guessed from the chart style by default
+    #[arg(long, value_enum, default_value_t = StagePreset::Auto)]
+    stage: StagePreset,
+    /// Player style, selecting the cost weights and rules
+    #[arg(long, value_enum, default_value_t = ProfilePreset::Default)]
+    profile: ProfilePreset,
+    /// JSON file with the cost weights (e.g written by the `fit` command), replacing the ones of the profile
+    #[arg(long)]
+    weights: Option<PathBuf>,
+    /// Moves never performed, unless the chart leaves no other choice
+    #[arg(long, value_enum, value_delimiter = ',')]
+    forbid: Vec<Constraint>,
+}
+
+impl ModelArgs {
+    fn predictor(&self) -> Result<Predictor, String> {
+        let mut builder = Predictor::builder().profile(self.profile.profile());
+        if let Some(path) = &self.weights {
+            builder = builder.cost_weights(load_weights(path)?);
+        }
+        for constraint in &self.forbid {
+            for &tech in constraint.tech() {
+                builder = builder.forbid(tech);
+            }
+        }
+        builder = match self.stage {
+            StagePreset::Auto => builder,
+            StagePreset::DdrSolo => builder.stage(DanceStage::ddr_solo()),
+            StagePreset::DdrDouble => builder.stage(DanceStage::ddr_double()),
+            StagePreset::PumpSingle => builder.stage(DanceStage::pump_single()),
+        };
+        Ok(builder.build())
     }
+}
+
+fn default_jobs() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum StagePreset {
+    Auto,
+    DdrSolo,
+    DdrDouble,
+    PumpSingle,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum ProfilePreset {
+    Default,
+    Beginner,
+    Tech,
+    Bracket,
+    BarUser,
+}
+
+impl ProfilePreset {
+    fn profile(self) -> Profile {
+        match self {
+            ProfilePreset::Default => Profile::Default,
+            ProfilePreset::Beginner => Profile::Beginner,
+            ProfilePreset::Tech => Profile::Tech,
+            ProfilePreset::Bracket => Profile::Bracket,
+            ProfilePreset::BarUser => Profile::BarUser,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Constraint {
+    Doublestep,
+    /// Front and back crossovers, and spins
+    Crossover,
+    Bracket,
+    /// Footswitches and sideswitches
+    Footswitch,
+}
+
+impl Constraint {
+    fn tech(self) -> &'static [Tech] {
+        match self {
+            Constraint::Doublestep => &[Tech::Doublestep],
+            Constraint::Crossover => &[Tech::FrontCrossover, Tech::BackCrossover, Tech::Spin],
+            Constraint::Bracket => &[Tech::Bracket],
+            Constraint::Footswitch => &[Tech::Footswitch, Tech::Sideswitch],
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl OutputFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "txt",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+        }
+    }
+
+    fn write<W: Write>(self, mut writer: W, steps: &[StepEntry]) -> std::io::Result<()> {
+        match self {
+            OutputFormat::Text => {
+                for step in steps {
+                    writeln!(
+                        writer,
+                        "{}\t{:.3}\t{}\t{}",
+                        step.row_index, step.time, step.columns, step.tech
+                    )?;
+                }
+                Ok(())
+            }
+            OutputFormat::Json => export::write_json(writer, steps),
+            OutputFormat::Csv => export::write_csv(writer, steps),
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum ReportFormat {
+    Text,
+    Json,
+}
+
+fn is_ssc(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ssc"))
+}
+
+fn read_simfile(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("Failed to read `{}`: {e}", path.display()))
+}
+
+fn parse_chart(path: &Path, simfile: &str) -> Result<SMChart, String> {
+    let simfile = if is_ssc(path) {
+        ssc_to_sm(simfile)
+    } else {
+        simfile.to_string()
+    };
+    SMChart::from_sm(simfile.as_bytes())
+        .map_err(|e| format!("Failed to parse `{}`: {e:?}", path.display()))
+}
+
+fn load_chart(path: &Path) -> Result<SMChart, String> {
+    parse_chart(path, &read_simfile(path)?)
+}
+
+/// Footing annotated for each difficulty of a chart, see [`read_footings`].
+type Footings = Vec<Option<Vec<FootPlacement>>>;
+
+/// Load a chart along with the footing annotated for each of its difficulties.
+fn load_annotated_chart(path: &Path) -> Result<(SMChart, Footings), String> {
+    let simfile = read_simfile(path)?;
+    let footings = read_footings(&simfile)
+        .map_err(|e| format!("Failed to read the footings of `{}`: {e}", path.display()))?;
+    Ok((parse_chart(path, &simfile)?, footings))
+}
+
+fn load_weights(path: &Path) -> Result<CostWeights, String> {
+    let weights = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read `{}`: {e}", path.display()))?;
+    serde_json::from_str(&weights)
+        .map_err(|e| format!("Invalid weights in `{}`: {e}", path.display()))
+}
+
+fn build_graph(
+    chart: &SMChart,
+    notes_data: &NotesData,
+    predictor: &Predictor,
+    time_budget: Option<f64>,
+) -> Result<StepGraph, String> {
+    let stage = predictor
+        .stage_for(&notes_data.style)
+        .map_err(|e| e.to_string())?;
+    let mut graph = predictor.new_graph(stage);
+    if let Some(time_budget) = time_budget {
+        let time_budget = Duration::try_from_secs_f64(time_budget)
+            .map_err(|e| format!("Invalid time budget: {e}"))?;
+        graph.set_time_budget(time_budget);
+    }
+    for NoteView { time, row, .. } in NoteViewer::new(chart, notes_data) {
+        graph
+            .try_append(time, row)
+            .map_err(|e| format!("Failed to build the step graph: {e}"))?;
+    }
+    Ok(graph)
+}
+
+fn solve(graph: &mut StepGraph) -> Result<Vec<StepEntry>, String> {
+    let steps = graph
+        .try_compute_steps()
+        .map_err(|e| format!("Failed to solve: {e}"))?;
+    if graph.budget_exceeded() {
+        eprintln!("Time budget exceeded, the footing may not be optimal");
+    }
+    if !graph.rule_violations().is_empty() {
+        eprintln!(
+            "The chart forces breaking the rules at rows {:?}",
+            graph.rule_violations()
+        );
+    }
+    Ok(steps)
+}
+
+fn select_notes_data<'a>(
+    chart: &'a SMChart,
+    difficulty: Option<&str>,
+    style: Option<&str>,
+) -> Result<&'a NotesData, String> {
+    let candidates = chart
+        .notes
+        .iter()
+        .filter(|n| style.is_none_or(|style| n.style.eq_ignore_ascii_case(style)))
+        .collect::<Vec<_>>();
+
+    let selected = match difficulty {
+        None => candidates.first().copied(),
+        Some(difficulty) => match difficulty.parse::<usize>() {
+            Ok(index) => candidates.get(index).copied(),
+            Err(_) => candidates.iter().copied().find(|n| {
+                n.difficulty.eq_ignore_ascii_case(difficulty)
+                    || n.description.eq_ignore_ascii_case(difficulty)
+            }),
+        },
+    };
+
+    selected.ok_or_else(|| {
+        let available = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, n)| format!("{i}: {} {} ({})", n.style, n.difficulty, n.description))
+            .collect::<Vec<_>>();
+        format!(
+            "No matching difficulty, available difficulties:\n{}",
+            available.join("\n")
+        )
+    })
+}
+
+fn predict(args: PredictArgs) -> Result<(), String> {
+    let chart = load_chart(&args.chart)?;
+    let notes_data = select_notes_data(&chart, args.difficulty.as_deref(), args.style.as_deref())?;
+
+    let mut graph = build_graph(
+        &chart,
+        notes_data,
+        &args.model.predictor()?,
+        args.time_budget,
+    )?;
+
+    if let Some(path) = &args.dot {
+        let rows = args.dot_from..args.dot_to.unwrap_or(usize::MAX);
+        std::fs::write(path, graph.to_dot(rows))
+            .map_err(|e| format!("Failed to write `{}`: {e}", path.display()))?;
+    }
+
+    let steps = solve(&mut graph)?;
+    args.format
+        .write(std::io::stdout().lock(), &steps)
+        .map_err(|e| format!("Failed to write steps: {e}"))
+}
+
+#[derive(Serialize)]
+struct ChartReport {
+    path: PathBuf,
+    index: usize,
+    style: String,
+    difficulty: String,
+    description: String,
+    meter: u8,
+    output: PathBuf,
+    stats: ChartStats,
+    estimate: DifficultyEstimate,
+    /// Rows where the chart forced breaking the rules.
+    rule_violations: Vec<usize>,
+}
+
+#[derive(Serialize)]
+struct Skipped {
+    path: PathBuf,
+    index: usize,
+    style: String,
+}
+
+#[derive(Serialize)]
+struct Failure {
+    path: PathBuf,
+    /// `None` if the whole file couldn't be loaded.
+    index: Option<usize>,
+    error: String,
+}
+
+#[derive(Serialize, Default)]
+struct BatchReport {
+    charts: Vec<ChartReport>,
+    skipped: Vec<Skipped>,
+    failures: Vec<Failure>,
+}
+
+/// Find every simfile in the directory, preferring the `.ssc` file when a song has both,
+/// the same way StepMania does.
+fn find_simfiles(directory: &Path, simfiles: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut entries = std::fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in &entries {
+        if path.is_dir() {
+            find_simfiles(path, simfiles)?;
+            continue;
+        }
+
+        let Some(extension) = path.extension() else {
+            continue;
+        };
+        if extension.eq_ignore_ascii_case("sm") && entries.contains(&path.with_extension("ssc")) {
+            continue;
+        }
+        if extension.eq_ignore_ascii_case("sm") || extension.eq_ignore_ascii_case("ssc") {
+            simfiles.push(path.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Simfiles given directly, or found in the given directories.
+fn collect_simfiles(paths: &[PathBuf]) -> Result<Vec<PathBuf>, String> {
+    let mut simfiles = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            find_simfiles(path, &mut simfiles)
+                .map_err(|e| format!("Failed to read `{}`: {e}", path.display()))?;
+        } else {
+            simfiles.push(path.clone());
+        }
+    }
+    Ok(simfiles)
+}
+
+fn batch_chart(
+    args: &BatchArgs,
+    predictor: &Predictor,
+    path: &Path,
+    index: usize,
+    chart: &SMChart,
+    notes_data: &NotesData,
+    stage: &DanceStage,
+) -> Result<ChartReport, String> {
+    let mut graph = build_graph(chart, notes_data, predictor, args.time_budget)?;
+    let steps = solve(&mut graph)?;
+
+    let relative = path.strip_prefix(&args.directory).unwrap_or(path);
+    let file_name = format!("{index}-{}", notes_data.difficulty.to_lowercase())
+        .replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_");
+    let output = args
+        .output
+        .join(relative.with_extension(""))
+        .join(file_name)
+        .with_extension(args.format.extension());
+
+    let write = || -> std::io::Result<()> {
+        std::fs::create_dir_all(output.parent().unwrap())?;
+        let file = std::fs::File::create(&output)?;
+        args.format.write(std::io::BufWriter::new(file), &steps)
+    };
+    write().map_err(|e| format!("Failed to write `{}`: {e}", output.display()))?;
+
+    let stats = ChartStats::new(stage, &steps);
+    let estimate = DifficultyEstimate::new(&stats, &steps);
+    Ok(ChartReport {
+        path: path.to_path_buf(),
+        index,
+        style: notes_data.style.clone(),
+        difficulty: notes_data.difficulty.clone(),
+        description: notes_data.description.clone(),
+        meter: notes_data.chart_meter,
+        output,
+        stats,
+        estimate,
+        rule_violations: graph.rule_violations().to_vec(),
+    })
+}
+
+fn batch(args: BatchArgs) -> Result<(), String> {
+    let mut simfiles = Vec::new();
+    find_simfiles(&args.directory, &mut simfiles)
+        .map_err(|e| format!("Failed to read `{}`: {e}", args.directory.display()))?;
+
+    let charts = parallel_map(&simfiles, args.jobs, |path| load_chart(path));
+
+    let predictor = args.model.predictor()?;
+    let mut report = BatchReport::default();
+    let mut jobs = Vec::new();
+    for (path, chart) in simfiles.iter().zip(&charts) {
+        let chart = match chart {
+            Ok(chart) => chart,
+            Err(error) => {
+                report.failures.push(Failure {
+                    path: path.clone(),
+                    index: None,
+                    error: error.clone(),
+                });
+                continue;
+            }
+        };
+
+        // Same classification as `Predictor::predict_all`, a forced stage supports every style
+        for (index, notes_data) in chart.notes.iter().enumerate() {
+            match predictor.stage_for(&notes_data.style) {
+                Ok(stage) => jobs.push((path, index, chart, notes_data, stage)),
+                Err(Error::UnsupportedStyle(style)) => report.skipped.push(Skipped {
+                    path: path.clone(),
+                    index,
+                    style,
+                }),
+                Err(error) => report.failures.push(Failure {
+                    path: path.clone(),
+                    index: Some(index),
+                    error: error.to_string(),
+                }),
+            }
+        }
+    }
+
+    let results = parallel_map(
+        &jobs,
+        args.jobs,
+        |&(path, index, chart, notes_data, ref stage)| {
+            batch_chart(&args, &predictor, path, index, chart, notes_data, stage)
+        },
+    );
+    for (&(path, index, ..), result) in jobs.iter().zip(results) {
+        match result {
+            Ok(chart_report) => report.charts.push(chart_report),
+            Err(error) => report.failures.push(Failure {
+                path: path.clone(),
+                index: Some(index),
+                error,
+            }),
+        }
+    }
+
+    let report_path = args.output.join("report.json");
+    std::fs::create_dir_all(&args.output)
+        .and_then(|_| {
+            let file = std::fs::File::create(&report_path)?;
+            serde_json::to_writer_pretty(std::io::BufWriter::new(file), &report)
+                .map_err(std::io::Error::from)
+        })
+        .map_err(|e| format!("Failed to write `{}`: {e}", report_path.display()))?;
+
+    for failure in &report.failures {
+        match failure.index {
+            Some(index) => eprintln!("{} [{index}]: {}", failure.path.display(), failure.error),
+            None => eprintln!("{}", failure.error),
+        }
+    }
+    eprintln!(
+        "Predicted {} charts, skipped {} unsupported charts, {} failures",
+        report.charts.len(),
+        report.skipped.len(),
+        report.failures.len()
+    );
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ChartEvaluation {
+    path: PathBuf,
+    index: usize,
+    style: String,
+    difficulty: String,
+    description: String,
+    evaluation: Evaluation,
+}
+
+#[derive(Serialize, Default)]
+struct EvalReport {
+    charts: Vec<ChartEvaluation>,
+    /// Accuracy over every chart.
+    total: Accuracy,
+    failures: Vec<Failure>,
+}
+
+fn write_eval_text<W: Write>(mut writer: W, report: &EvalReport) -> std::io::Result<()> {
+    let write_accuracy = |writer: &mut W, accuracy: &Accuracy| {
+        writeln!(
+            writer,
+            "rows {:.1}% ({}/{}), feet {:.1}% ({}/{})",
+            accuracy.row_accuracy() * 100.0,
+            accuracy.matching_rows,
+            accuracy.rows,
+            accuracy.foot_accuracy() * 100.0,
+            accuracy.matching_feet,
+            accuracy.feet
+        )
+    };
+    let tech_name = |tech: Option<Tech>| tech.map_or("step", |tech| tech.name());
+
+    for chart in &report.charts {
+        write!(
+            writer,
+            "{} [{}] {} {}: ",
+            chart.path.display(),
+            chart.index,
+            chart.style,
+            chart.difficulty
+        )?;
+        write_accuracy(&mut writer, &chart.evaluation.accuracy)?;
+        for section in &chart.evaluation.worst_sections {
+            writeln!(
+                writer,
+                "    rows {}..{} ({:.3}s - {:.3}s): {} mismatches",
+                section.rows.start,
+                section.rows.end,
+                section.start_time,
+                section.end_time,
+                section.mismatches
+            )?;
+        }
+    }
+
+    write!(writer, "Total: ")?;
+    write_accuracy(&mut writer, &report.total)?;
+    for confusion in &report.total.confusions {
+        writeln!(
+            writer,
+            "    {} predicted as {}: {}",
+            tech_name(confusion.expected),
+            tech_name(confusion.predicted),
+            confusion.count
+        )?;
+    }
+    Ok(())
+}
+
+fn eval(args: EvalArgs) -> Result<(), String> {
+    let simfiles = collect_simfiles(&args.paths)?;
+    let charts = parallel_map(&simfiles, args.jobs, |path| load_annotated_chart(path));
+
+    let mut report = EvalReport::default();
+    let mut jobs = Vec::new();
+    for (path, chart) in simfiles.iter().zip(&charts) {
+        let (chart, footings) = match chart {
+            Ok(chart) => chart,
+            Err(error) => {
+                report.failures.push(Failure {
+                    path: path.clone(),
+                    index: None,
+                    error: error.clone(),
+                });
+                continue;
+            }
+        };
+
+        for (index, (notes_data, footing)) in chart.notes.iter().zip(footings).enumerate() {
+            if let Some(footing) = footing {
+                jobs.push((path, index, chart, notes_data, footing));
+            }
+        }
+    }
+
+    let predictor = args.model.predictor()?;
+    let results = parallel_map(&jobs, args.jobs, |&(_, _, chart, notes_data, footing)| {
+        predictor.evaluate(chart, notes_data, footing)
+    });
+    for (&(path, index, _, notes_data, _), result) in jobs.iter().zip(results) {
+        match result {
+            Ok(evaluation) => {
+                report.total.add(&evaluation.accuracy);
+                report.charts.push(ChartEvaluation {
+                    path: path.clone(),
+                    index,
+                    style: notes_data.style.clone(),
+                    difficulty: notes_data.difficulty.clone(),
+                    description: notes_data.description.clone(),
+                    evaluation,
+                });
+            }
+            Err(error) => report.failures.push(Failure {
+                path: path.clone(),
+                index: Some(index),
+                error: error.to_string(),
+            }),
+        }
+    }
+
+    let stdout = std::io::stdout().lock();
+    match args.format {
+        ReportFormat::Text => write_eval_text(stdout, &report),
+        ReportFormat::Json => {
+            serde_json::to_writer_pretty(stdout, &report).map_err(std::io::Error::from)
+        }
+    }
+    .map_err(|e| format!("Failed to write the report: {e}"))?;
+
+    for failure in &report.failures {
+        match failure.index {
+            Some(index) => eprintln!("{} [{index}]: {}", failure.path.display(), failure.error),
+            None => eprintln!("{}", failure.error),
+        }
+    }
+    if report.charts.is_empty() {
+        return Err("No chart with an annotated footing was found".to_string());
+    }
+
+    Ok(())
+}
+
+fn fit(args: FitArgs) -> Result<(), String> {
+    let simfiles = collect_simfiles(&args.paths)?;
+    let charts = parallel_map(&simfiles, args.jobs, |path| load_annotated_chart(path));
+
+    let mut annotated = Vec::new();
+    let mut sources = Vec::new();
+    for (path, chart) in simfiles.iter().zip(&charts) {
+        let (chart, footings) = match chart {
+            Ok(chart) => chart,
+            Err(error) => {
+                eprintln!("{error}");
+                continue;
+            }
+        };
+
+        for (index, (notes_data, footing)) in chart.notes.iter().zip(footings).enumerate() {
+            if let Some(footing) = footing {
+                annotated.push(AnnotatedChart {
+                    chart,
+                    notes_data,
+                    footing,
+                });
+                sources.push((path, index));
+            }
+        }
+    }
+    if annotated.is_empty() {
+        return Err("No chart with an annotated footing was found".to_string());
+    }
+
+    let predictor = args.model.predictor()?;
+    let options = FitOptions {
+        max_rounds: args.rounds,
+        workers: args.jobs,
+        ..FitOptions::default()
+    };
+    let report = fit_weights(&predictor, &annotated, &options)
+        .map_err(|e| format!("Failed to fit the weights: {e}"))?;
+
+    for (chart, error) in &report.failures {
+        let (path, index) = sources[*chart];
+        eprintln!("{} [{index}]: {error}", path.display());
+    }
+    println!(
+        "Fitted {} charts in {} rounds: rows {:.1}% -> {:.1}%, feet {:.1}% -> {:.1}%",
+        annotated.len() - report.failures.len(),
+        report.rounds,
+        report.initial_accuracy.row_accuracy() * 100.0,
+        report.accuracy.row_accuracy() * 100.0,
+        report.initial_accuracy.foot_accuracy() * 100.0,
+        report.accuracy.foot_accuracy() * 100.0
+    );
+    for change in &report.changes {
+        println!(
+            "{:<16}{:>10.2} -> {:>10.2} (x{:.3})",
+            change.term,
+            change.initial,
+            change.fitted,
+            change.ratio()
+        );
+    }
+
+    if let Some(output) = &args.output {
+        std::fs::File::create(output)
+            .and_then(|file| {
+                serde_json::to_writer_pretty(std::io::BufWriter::new(file), &report.weights)
+                    .map_err(std::io::Error::from)
+            })
+            .map_err(|e| format!("Failed to write `{}`: {e}", output.display()))?;
+    }
+
+    Ok(())
+}
 
-    write!(
-        std::fs::File::create("out.txt").unwrap(),
-        "{}",
-        Dot::with_config(&graph.graph, &[])
-    )
-    .unwrap();
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Predict(args) => predict(args),
+        Command::Batch(args) => batch(args),
+        Command::Eval(args) => eval(args),
+        Command::Fit(args) => fit(args),
+    };
 
-    let steps = graph.compute_steps();
-    for step in steps {
-        println!("{}", step.columns);
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
     }
 }
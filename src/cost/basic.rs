@@ -1,7 +1,8 @@
 use danceparser::NoteKind;
 
 use crate::FootPart;
-use crate::cost::{CostParams, DOUBLESTEP_COST, FACING_COST, MINE_COST, MOVEMENT_COST};
+use crate::cost::CostParams;
+use crate::error::Error;
 use crate::feet::{FootPartIndices, Side};
 
 pub fn movement_cost(
@@ -12,7 +13,7 @@ pub fn movement_cost(
         dt,
         ..
     }: CostParams,
-) -> f64 {
+) -> Result<f64, Error> {
     let mut cost = 0.0;
 
     for part in FootPart::all_except_none() {
@@ -25,8 +26,9 @@ pub fn movement_cost(
             continue;
         };
 
+        // An activated foot part should always have a placement in the final state
         let Some(next_position) = next.final_columns.get_foot_part_index(part) else {
-            panic!("An activated foot part should have a placement in the final state")
+            return Err(Error::InconsistentState);
         };
 
         // For tap -> bracket transitions: we don't incur movement cost twice
@@ -36,10 +38,10 @@ pub fn movement_cost(
         }
 
         let velocity = stage.distance_between(prev_position, next_position) / dt;
-        cost += velocity * MOVEMENT_COST;
+        cost += velocity;
     }
 
-    cost
+    Ok(cost)
 }
 
 pub fn facing_cost(CostParams { stage, next, .. }: CostParams) -> f64 {
@@ -83,7 +85,7 @@ pub fn facing_cost(CostParams { stage, next, .. }: CostParams) -> f64 {
     cost += penalty(toe_facing);
     cost += penalty(left_facing);
     cost += penalty(right_facing);
-    cost * FACING_COST
+    cost
 }
 
 pub fn doublestep_cost(
@@ -125,7 +127,7 @@ pub fn doublestep_cost(
             if jacked {
                 return 0.0;
             }
-            DOUBLESTEP_COST * stage.distance_between(ph, nh).powi(3)
+            stage.distance_between(ph, nh).powi(3)
         }
         _ => 0.0,
     }
@@ -138,8 +140,9 @@ pub fn mine_cost(
 ) -> f64 {
     let mut cost = 0.0;
     for column in 0..stage.column_count() {
-        if row.columns[column] == NoteKind::Mine && next.final_columns.0[column] != FootPart::None {
-            cost += MINE_COST;
+        if row.columns[column] == NoteKind::Mine && next.final_columns.at(column) != FootPart::None
+        {
+            cost += 1.0;
         }
     }
     cost
@@ -1,4 +1,4 @@
-use crate::cost::{CostParams, SLOW_BRACKET_COST, SLOW_BRACKET_THRESHOLD, TWISTED_FOOT_COST};
+use crate::cost::{CostParams, SLOW_BRACKET_THRESHOLD};
 use crate::feet::{FootPartIndices, Side};
 use crate::stage::StagePosition;
 
@@ -38,7 +38,7 @@ pub fn twisted_foot_cost(CostParams { stage, next, .. }: CostParams) -> f64 {
         return 0.0;
     }
 
-    TWISTED_FOOT_COST
+    1.0
 }
 
 pub fn slow_bracket_cost(CostParams { next, dt, .. }: CostParams) -> f64 {
@@ -53,6 +53,5 @@ pub fn slow_bracket_cost(CostParams { next, dt, .. }: CostParams) -> f64 {
         return 0.0;
     }
 
-    let time_diff = dt - SLOW_BRACKET_THRESHOLD;
-    time_diff * SLOW_BRACKET_COST
+    dt - SLOW_BRACKET_THRESHOLD
 }
@@ -1,8 +1,5 @@
 use crate::FootPart;
-use crate::cost::{
-    CostParams, JACK_COST, JACK_THRESHOLD, SIDESWITCH_COST, SLOW_FOOTSWITCH_COST,
-    SLOW_FOOTSWITCH_THRESHOLD,
-};
+use crate::cost::{CostParams, JACK_THRESHOLD, SLOW_FOOTSWITCH_THRESHOLD};
 use crate::feet::Side;
 
 fn jacked_side(CostParams { prev, next, .. }: CostParams, side: Side) -> bool {
@@ -16,14 +13,14 @@ fn jacked_side(CostParams { prev, next, .. }: CostParams, side: Side) -> bool {
         return false;
     };
 
-    let jacked_heel = prev.final_columns.0[heel_idx] == heel;
-    let jacked_toe = prev.final_columns.0[toe_idx] == toe;
+    let jacked_heel = prev.final_columns.at(heel_idx) == heel;
+    let jacked_toe = prev.final_columns.at(toe_idx) == toe;
 
     jacked_heel || jacked_toe
 }
 
 pub fn jack_cost(params @ CostParams { dt, .. }: CostParams) -> f64 {
-    if dt > JACK_THRESHOLD {
+    if dt >= JACK_THRESHOLD {
         return 0.0;
     }
 
@@ -38,10 +35,11 @@ pub fn jack_cost(params @ CostParams { dt, .. }: CostParams) -> f64 {
         cost += time_cost;
     }
 
-    cost * JACK_COST
+    cost
 }
 
-pub fn footswitch_cost(
+/// Whether a slow footswitch happened, and whether it happened on a side panel.
+fn slow_footswitch(
     CostParams {
         stage,
         prev,
@@ -49,46 +47,40 @@ pub fn footswitch_cost(
         dt,
         ..
     }: CostParams,
-) -> f64 {
+) -> Option<bool> {
     if dt < SLOW_FOOTSWITCH_THRESHOLD {
-        return 0.0;
+        return None;
     }
 
     let activated_one_side_only =
         next.side_activated(Side::Left) ^ next.side_activated(Side::Right);
     if !activated_one_side_only {
-        return 0.0;
+        return None;
     }
 
-    let mut has_footswitch = false;
-    let mut has_sideswitch = false;
-    for column in 0..stage.column_count() {
-        // Ignore column if there was no foot on it, or no foot is hitting it (i.e not a footswitch)
-        if prev.final_columns.0[column] == FootPart::None
-            || next.activated_columns.0[column] == FootPart::None
-        {
-            continue;
-        }
-
-        if prev.final_columns.0[column].side() != next.activated_columns.0[column].side() {
-            has_footswitch = true;
-            if stage.is_side_panel(column) {
-                has_sideswitch = true;
-            }
-            break;
-        }
-    }
+    (0..stage.column_count())
+        .find(|&column| {
+            // Ignore column if there was no foot on it, or no foot is hitting it (i.e not a footswitch)
+            prev.final_columns.at(column) != FootPart::None
+                && next.activated_columns.at(column) != FootPart::None
+                && prev.final_columns.at(column).side() != next.activated_columns.at(column).side()
+        })
+        .map(|column| stage.is_side_panel(column))
+}
 
-    if !has_footswitch {
+pub fn footswitch_cost(params @ CostParams { dt, .. }: CostParams) -> f64 {
+    if slow_footswitch(params).is_none() {
         return 0.0;
     }
 
-    let time_diff = SLOW_FOOTSWITCH_THRESHOLD - dt;
-    let time_cost = time_diff / (SLOW_FOOTSWITCH_THRESHOLD + time_diff);
+    let time_diff = dt - SLOW_FOOTSWITCH_THRESHOLD;
+    time_diff / (SLOW_FOOTSWITCH_THRESHOLD + time_diff)
+}
 
-    let mut cost = time_cost * SLOW_FOOTSWITCH_COST;
-    if has_sideswitch {
-        cost += SIDESWITCH_COST;
+pub fn sideswitch_cost(params: CostParams) -> f64 {
+    if slow_footswitch(params) == Some(true) {
+        1.0
+    } else {
+        0.0
     }
-    cost
 }
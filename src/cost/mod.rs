@@ -2,30 +2,156 @@ mod basic;
 mod brackets;
 mod footswitch_jacks;
 
-use crate::cost::footswitch_jacks::footswitch_cost;
+use crate::error::Error;
 use crate::stage::DanceStage;
 use crate::state::State;
 use basic::*;
 use brackets::*;
 use danceparser::Row;
 use footswitch_jacks::*;
+use std::fmt::Display;
 
 // TODO Don't ignore holds for costs (esp. when it comes to doublesteps).
 
-const MOVEMENT_COST: f64 = 6.0;
-const FACING_COST: f64 = 2000.0;
-const DOUBLESTEP_COST: f64 = 850.0;
-const MINE_COST: f64 = 10000.0;
-const TWISTED_FOOT_COST: f64 = 1000.0;
-const SLOW_BRACKET_COST: f64 = 300.0;
-const JACK_COST: f64 = 30.0;
-const SLOW_FOOTSWITCH_COST: f64 = 325.0;
-const SIDESWITCH_COST: f64 = 130.0;
-
 const SLOW_BRACKET_THRESHOLD: f64 = 0.15;
 const JACK_THRESHOLD: f64 = 0.1;
 const SLOW_FOOTSWITCH_THRESHOLD: f64 = 0.2;
 
+/// Rows closer than this (in seconds) are considered simultaneous.
+///
+/// This also covers rows reached through warps, or going back in time,
+/// which would otherwise give a zero or negative time difference.
+const MIN_DT: f64 = 0.001;
+
+/// A single term of the cost of a transition between two states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CostTerm {
+    /// Moving a foot, proportional to its speed.
+    Movement,
+    /// Facing backwards, or with the feet twisted inwards.
+    Facing,
+    /// Stepping twice in a row with the same foot.
+    Doublestep,
+    /// Leaving a foot on a mine.
+    Mine,
+    /// Facing backwards with a single foot without crossing over.
+    TwistedFoot,
+    /// Bracketing notes slow enough to be stepped separately.
+    SlowBracket,
+    /// Stepping twice on the same column too quickly.
+    Jack,
+    /// Switching feet on a column slow enough to be alternated.
+    SlowFootswitch,
+    /// Additional cost of a footswitch on a side panel.
+    Sideswitch,
+}
+
+impl CostTerm {
+    pub const ALL: [CostTerm; 9] = [
+        CostTerm::Movement,
+        CostTerm::Facing,
+        CostTerm::Doublestep,
+        CostTerm::Mine,
+        CostTerm::TwistedFoot,
+        CostTerm::SlowBracket,
+        CostTerm::Jack,
+        CostTerm::SlowFootswitch,
+        CostTerm::Sideswitch,
+    ];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            CostTerm::Movement => "movement",
+            CostTerm::Facing => "facing",
+            CostTerm::Doublestep => "doublestep",
+            CostTerm::Mine => "mine",
+            CostTerm::TwistedFoot => "twisted-foot",
+            CostTerm::SlowBracket => "slow-bracket",
+            CostTerm::Jack => "jack",
+            CostTerm::SlowFootswitch => "slow-footswitch",
+            CostTerm::Sideswitch => "sideswitch",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|term| term.name() == s)
+    }
+}
+
+impl Display for CostTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// Weight of every cost term, a weight of zero disables the term.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CostWeights {
+    pub movement: f64,
+    pub facing: f64,
+    pub doublestep: f64,
+    pub mine: f64,
+    pub twisted_foot: f64,
+    pub slow_bracket: f64,
+    pub jack: f64,
+    pub slow_footswitch: f64,
+    pub sideswitch: f64,
+}
+
+impl Default for CostWeights {
+    fn default() -> Self {
+        CostWeights {
+            movement: 6.0,
+            facing: 2000.0,
+            doublestep: 850.0,
+            mine: 10000.0,
+            twisted_foot: 1000.0,
+            slow_bracket: 300.0,
+            jack: 30.0,
+            slow_footswitch: 325.0,
+            sideswitch: 130.0,
+        }
+    }
+}
+
+impl CostWeights {
+    pub fn get(&self, term: CostTerm) -> f64 {
+        match term {
+            CostTerm::Movement => self.movement,
+            CostTerm::Facing => self.facing,
+            CostTerm::Doublestep => self.doublestep,
+            CostTerm::Mine => self.mine,
+            CostTerm::TwistedFoot => self.twisted_foot,
+            CostTerm::SlowBracket => self.slow_bracket,
+            CostTerm::Jack => self.jack,
+            CostTerm::SlowFootswitch => self.slow_footswitch,
+            CostTerm::Sideswitch => self.sideswitch,
+        }
+    }
+
+    pub fn get_mut(&mut self, term: CostTerm) -> &mut f64 {
+        match term {
+            CostTerm::Movement => &mut self.movement,
+            CostTerm::Facing => &mut self.facing,
+            CostTerm::Doublestep => &mut self.doublestep,
+            CostTerm::Mine => &mut self.mine,
+            CostTerm::TwistedFoot => &mut self.twisted_foot,
+            CostTerm::SlowBracket => &mut self.slow_bracket,
+            CostTerm::Jack => &mut self.jack,
+            CostTerm::SlowFootswitch => &mut self.slow_footswitch,
+            CostTerm::Sideswitch => &mut self.sideswitch,
+        }
+    }
+
+    /// Same weights, with `term` disabled.
+    pub fn without(mut self, term: CostTerm) -> Self {
+        *self.get_mut(term) = 0.0;
+        self
+    }
+}
+
 #[derive(Copy, Clone)]
 #[non_exhaustive]
 struct CostParams<'a> {
@@ -36,24 +162,125 @@ struct CostParams<'a> {
     dt: f64,
 }
 
-pub fn total_cost(stage: &DanceStage, row: &Row, prev: &State, next: &State, dt: f64) -> f64 {
+/// Unweighted value of every cost term, in the order of [`CostTerm::ALL`].
+pub fn term_costs(
+    stage: &DanceStage,
+    row: &Row,
+    prev: &State,
+    next: &State,
+    dt: f64,
+) -> Result<[f64; CostTerm::ALL.len()], Error> {
     let params = CostParams {
         stage,
         row,
         prev,
         next,
-        dt,
+        // `f64::max` also replaces NaN
+        dt: dt.max(MIN_DT),
     };
 
-    let mut cost = 0.0;
-    cost += movement_cost(params);
-    cost += facing_cost(params);
-    cost += doublestep_cost(params);
-    cost += mine_cost(params);
-    cost += twisted_foot_cost(params);
-    cost += slow_bracket_cost(params);
-    cost += jack_cost(params);
-    cost += footswitch_cost(params);
-
-    cost
+    let mut costs = [0.0; CostTerm::ALL.len()];
+    for (cost, term) in costs.iter_mut().zip(CostTerm::ALL) {
+        *cost = match term {
+            CostTerm::Movement => movement_cost(params)?,
+            CostTerm::Facing => facing_cost(params),
+            CostTerm::Doublestep => doublestep_cost(params),
+            CostTerm::Mine => mine_cost(params),
+            CostTerm::TwistedFoot => twisted_foot_cost(params),
+            CostTerm::SlowBracket => slow_bracket_cost(params),
+            CostTerm::Jack => jack_cost(params),
+            CostTerm::SlowFootswitch => footswitch_cost(params),
+            CostTerm::Sideswitch => sideswitch_cost(params),
+        };
+    }
+    Ok(costs)
+}
+
+pub fn total_cost(
+    weights: &CostWeights,
+    stage: &DanceStage,
+    row: &Row,
+    prev: &State,
+    next: &State,
+    dt: f64,
+) -> Result<f64, Error> {
+    let costs = term_costs(stage, row, prev, next, dt)?;
+    Ok(CostTerm::ALL
+        .into_iter()
+        .zip(costs)
+        .map(|(term, cost)| weights.get(term) * cost)
+        .sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feet::CompactPlacement;
+    use crate::graph::tests::tap;
+
+    fn state(prev: &State, placement: &str) -> State {
+        prev.append(&CompactPlacement::parse(placement))
+    }
+
+    #[test]
+    fn test_simultaneous_rows() {
+        let stage = DanceStage::ddr_solo();
+        let prev = state(&State::new(4), "L--R");
+        let next = state(&prev, "-L--");
+        let row = tap(1);
+
+        let min_dt_cost =
+            total_cost(&CostWeights::default(), &stage, &row, &prev, &next, MIN_DT).unwrap();
+        assert!(min_dt_cost.is_finite());
+        for dt in [0.0, MIN_DT / 2.0, -0.5, f64::NAN] {
+            assert_eq!(
+                total_cost(&CostWeights::default(), &stage, &row, &prev, &next, dt),
+                Ok(min_dt_cost)
+            );
+        }
+    }
+
+    #[test]
+    fn test_costs_are_finite_and_positive() {
+        let stage = DanceStage::ddr_solo();
+        let start = state(&State::new(4), "L--R");
+        let transitions = [
+            (state(&start, "L---"), tap(0)),
+            (state(&start, "R---"), tap(0)),
+            (state(&start, "-L--"), tap(1)),
+        ];
+
+        let dts = [-1.0, 0.0, 1e-9, 0.05, 0.1, 0.15, 0.2, 0.3, 0.4, 1.0, 10.0];
+        for (next, row) in &transitions {
+            for dt in [JACK_THRESHOLD, SLOW_FOOTSWITCH_THRESHOLD * 2.0]
+                .into_iter()
+                .chain(dts)
+            {
+                let cost =
+                    total_cost(&CostWeights::default(), &stage, row, &start, next, dt).unwrap();
+                assert!(cost.is_finite() && cost >= 0.0, "{next} at {dt}s: {cost}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_slow_footswitch_cost_grows_with_time() {
+        let stage = DanceStage::ddr_solo();
+        let prev = state(&State::new(4), "-R--");
+        let next = state(&prev, "-L--");
+        let row = tap(1);
+
+        let costs = [0.2, 0.3, 0.4, 1.0, 5.0].map(|dt| {
+            footswitch_cost(CostParams {
+                stage: &stage,
+                row: &row,
+                prev: &prev,
+                next: &next,
+                dt,
+            })
+        });
+        assert_eq!(costs[0], 0.0);
+        assert!(costs.windows(2).all(|w| w[0] < w[1]), "{costs:?}");
+        assert!(costs[4] < 1.0);
+    }
 }
@@ -0,0 +1,92 @@
+use crate::graph::StepEntry;
+use crate::stats::ChartStats;
+use crate::tech::Tech;
+
+// Calibrated against the meters of `Eientewi Set 12A.sm`.
+const COST_DENSITY_WEIGHT: f64 = 0.7;
+const NPS_WEIGHT: f64 = 1.0;
+const TECH_DENSITY_WEIGHT: f64 = 0.5;
+const RATING_OFFSET: f64 = -0.8;
+
+/// Estimated difficulty of a chart, on the same scale as the `.sm` meter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DifficultyEstimate {
+    pub rating: f64,
+    /// Total footing cost per second of chart.
+    pub cost_density: f64,
+    /// Average notes per second.
+    pub note_density: f64,
+    /// Tech (excluding jumps and hold steps) performed per second of chart.
+    pub tech_density: f64,
+}
+
+impl DifficultyEstimate {
+    pub fn new(stats: &ChartStats, steps: &[StepEntry]) -> Self {
+        if stats.duration <= 0.0 {
+            return DifficultyEstimate {
+                rating: 0.0,
+                cost_density: 0.0,
+                note_density: 0.0,
+                tech_density: 0.0,
+            };
+        }
+
+        let total_cost = steps.iter().map(|step| step.cost.0).sum::<f64>();
+        let cost_density = total_cost / stats.duration;
+
+        let tech_count = stats
+            .tech_counts
+            .iter()
+            .filter(|(tech, _)| !matches!(tech, Tech::Jump | Tech::HoldStep))
+            .map(|(_, &count)| count)
+            .sum::<usize>();
+        let tech_density = tech_count as f64 / stats.duration;
+
+        // The cost grows much faster than the perceived difficulty, so it's dampened with a log.
+        let rating = COST_DENSITY_WEIGHT * cost_density.ln_1p()
+            + NPS_WEIGHT * stats.average_nps
+            + TECH_DENSITY_WEIGHT * tech_density
+            + RATING_OFFSET;
+
+        DifficultyEstimate {
+            rating: rating.max(0.0),
+            cost_density,
+            note_density: stats.average_nps,
+            tech_density,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DanceStage, generate_steps};
+    use danceparser::SMChart;
+    use danceparser::view::NoteViewer;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_eientewi_calibration() {
+        let chart = SMChart::from_sm(Cursor::new(include_str!("../Eientewi Set 12A.sm")))
+            .expect("Failed to parse StepMania chart");
+
+        let mut prev_rating = f64::INFINITY;
+        for diff in &chart.notes {
+            let steps = generate_steps(NoteViewer::new(&chart, diff));
+            let stats = ChartStats::new(&DanceStage::ddr_solo(), &steps);
+            let estimate = DifficultyEstimate::new(&stats, &steps);
+
+            assert!(
+                (estimate.rating - diff.chart_meter as f64).abs() <= 1.0,
+                "{} is rated {}, estimated {:.2}",
+                diff.difficulty,
+                diff.chart_meter,
+                estimate.rating
+            );
+            // Difficulties are listed from hardest to easiest.
+            assert!(estimate.rating < prev_rating);
+            prev_rating = estimate.rating;
+        }
+    }
+}
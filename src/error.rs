@@ -0,0 +1,71 @@
+use crate::feet::FootPart;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A row doesn't have as many columns as the stage.
+    ColumnCount { expected: usize, actual: usize },
+    /// A stage position has a NaN coordinate.
+    NanPosition,
+    /// Two columns of a stage have the same position.
+    DuplicatePosition { first: usize, second: usize },
+    /// A foot placement has the same foot part on more than one column.
+    DuplicateFootPart(FootPart),
+    /// A stage or foot placement has more columns than supported.
+    TooManyColumns { max: usize, actual: usize },
+    /// A foot part was activated without being placed on the stage.
+    InconsistentState,
+    /// No footing could be found for the chart.
+    NoPath,
+    /// An annotated footing doesn't have as many rows as the chart.
+    RowCount { expected: usize, actual: usize },
+    /// A note isn't stepped on by the annotated footing.
+    UnsteppedNote { row_index: usize },
+    /// No stage is known for a chart style.
+    UnsupportedStyle(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ColumnCount { expected, actual } => {
+                write!(
+                    f,
+                    "Got a row with {actual} columns, expected {expected} columns"
+                )
+            }
+            Error::NanPosition => write!(f, "Stage position coordinates can't be NaN"),
+            Error::DuplicatePosition { first, second } => {
+                write!(
+                    f,
+                    "Columns {first} and {second} have the same stage position"
+                )
+            }
+            Error::DuplicateFootPart(part) => {
+                write!(f, "The foot part {part} is placed on more than one column")
+            }
+            Error::TooManyColumns { max, actual } => {
+                write!(
+                    f,
+                    "Got {actual} columns, at most {max} columns are supported"
+                )
+            }
+            Error::InconsistentState => {
+                write!(f, "A foot part was activated without being placed")
+            }
+            Error::NoPath => write!(f, "No footing could be found for the chart"),
+            Error::RowCount { expected, actual } => {
+                write!(
+                    f,
+                    "Got a footing with {actual} rows, expected {expected} rows"
+                )
+            }
+            Error::UnsteppedNote { row_index } => {
+                write!(f, "A note of row {row_index} isn't stepped on")
+            }
+            Error::UnsupportedStyle(style) => write!(f, "No stage is known for the style {style}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,373 @@
+//! Accuracy of predicted footings against annotated ones.
+//!
+//! Annotated footings use the same format as [`crate::read_footings`]: the final foot placement
+//! of every row. The foot parts stepping on each row are the ones placed on its notes.
+use crate::error::Error;
+use crate::extensions::HasPressRequirement;
+use crate::feet::{CompactPlacement, FootPart, FootPlacement, Side};
+use crate::graph::{StepEntry, build_steps};
+use crate::input::InputRow;
+use crate::predictor::Predictor;
+use crate::stage::DanceStage;
+use crate::state::State;
+use crate::tech::Tech;
+use danceparser::view::NoteViewer;
+use danceparser::{NotesData, Row, SMChart};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// Amount of rows in a section, see [`Evaluation::worst_sections`].
+const SECTION_ROWS: usize = 16;
+/// Amount of sections listed in [`Evaluation::worst_sections`].
+const WORST_SECTIONS: usize = 5;
+
+/// Rows where the annotation performs `expected` but the prediction performs `predicted` instead.
+///
+/// `None` stands for a step without any tech.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Confusion {
+    pub expected: Option<Tech>,
+    pub predicted: Option<Tech>,
+    pub count: usize,
+}
+
+/// Agreement between predicted and annotated footings.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Accuracy {
+    /// Rows stepped on in the annotation or the prediction.
+    pub rows: usize,
+    /// Rows stepped on with exactly the same foot parts.
+    pub matching_rows: usize,
+    /// Feet stepping in the annotation or the prediction, counted once per row.
+    pub feet: usize,
+    /// Feet stepping on exactly the same columns.
+    pub matching_feet: usize,
+    /// Sorted from the most to the least frequent.
+    pub confusions: Vec<Confusion>,
+}
+
+impl Accuracy {
+    pub fn row_accuracy(&self) -> f64 {
+        ratio(self.matching_rows, self.rows)
+    }
+
+    pub fn foot_accuracy(&self) -> f64 {
+        ratio(self.matching_feet, self.feet)
+    }
+
+    /// Add the counts of `other`, e.g to get the accuracy over many charts.
+    pub fn add(&mut self, other: &Accuracy) {
+        self.rows += other.rows;
+        self.matching_rows += other.matching_rows;
+        self.feet += other.feet;
+        self.matching_feet += other.matching_feet;
+
+        let mut confusions = BTreeMap::new();
+        for confusion in self.confusions.iter().chain(&other.confusions) {
+            *confusions
+                .entry((confusion.expected, confusion.predicted))
+                .or_default() += confusion.count;
+        }
+        self.confusions = sorted_confusions(confusions);
+    }
+}
+
+fn ratio(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+fn sorted_confusions(counts: BTreeMap<(Option<Tech>, Option<Tech>), usize>) -> Vec<Confusion> {
+    let mut confusions = counts
+        .into_iter()
+        .map(|((expected, predicted), count)| Confusion {
+            expected,
+            predicted,
+            count,
+        })
+        .collect::<Vec<_>>();
+    confusions.sort_by_key(|confusion| std::cmp::Reverse(confusion.count));
+    confusions
+}
+
+/// Consecutive rows of a chart.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Section {
+    pub rows: Range<usize>,
+    pub start_time: f64,
+    pub end_time: f64,
+    /// Rows of the section not matching the annotation.
+    pub mismatches: usize,
+}
+
+/// Evaluation of the predicted footing of a single chart.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Evaluation {
+    pub accuracy: Accuracy,
+    /// Sections with the most mismatching rows, from the worst one.
+    pub worst_sections: Vec<Section>,
+}
+
+/// Compare the predicted steps of a chart to its annotated footing.
+pub fn evaluate<R: InputRow>(
+    dance_stage: &DanceStage,
+    rows: impl IntoIterator<Item = R>,
+    annotated: &[FootPlacement],
+    predicted: &[StepEntry],
+) -> Result<Evaluation, Error> {
+    let rows = rows
+        .into_iter()
+        .map(|row| (row.time(), row.to_row()))
+        .collect::<Vec<_>>();
+    for len in [annotated.len(), predicted.len()] {
+        if len != rows.len() {
+            return Err(Error::RowCount {
+                expected: rows.len(),
+                actual: len,
+            });
+        }
+    }
+
+    let expected = annotated_steps(dance_stage, &rows, annotated)?;
+
+    let mut accuracy = Accuracy::default();
+    let mut confusions = BTreeMap::new();
+    let mut mismatching_rows = vec![false; rows.len()];
+    for ((expected, predicted), mismatch) in
+        expected.iter().zip(predicted).zip(&mut mismatching_rows)
+    {
+        let (expected_columns, predicted_columns) =
+            (&expected.activated_columns, &predicted.activated_columns);
+        if expected_columns.part_count() == 0 && predicted_columns.part_count() == 0 {
+            continue;
+        }
+
+        accuracy.rows += 1;
+        if expected_columns == predicted_columns {
+            accuracy.matching_rows += 1;
+        } else {
+            *mismatch = true;
+            for pair in confused_tech(expected, predicted) {
+                *confusions.entry(pair).or_default() += 1;
+            }
+        }
+
+        for side in [Side::Left, Side::Right] {
+            let side_columns = |columns: &FootPlacement| {
+                columns
+                    .0
+                    .iter()
+                    .map(|part| part.side() == Some(side))
+                    .collect::<Vec<_>>()
+            };
+            let (expected_side, predicted_side) = (
+                side_columns(expected_columns),
+                side_columns(predicted_columns),
+            );
+            if !expected_side.contains(&true) && !predicted_side.contains(&true) {
+                continue;
+            }
+
+            accuracy.feet += 1;
+            if expected_side == predicted_side {
+                accuracy.matching_feet += 1;
+            }
+        }
+    }
+    accuracy.confusions = sorted_confusions(confusions);
+
+    let mut sections = (0..rows.len())
+        .step_by(SECTION_ROWS)
+        .map(|start| {
+            let end = (start + SECTION_ROWS).min(rows.len());
+            Section {
+                rows: start..end,
+                start_time: rows[start].0,
+                end_time: rows[end - 1].0,
+                mismatches: mismatching_rows[start..end].iter().filter(|&&m| m).count(),
+            }
+        })
+        .filter(|section| section.mismatches > 0)
+        .collect::<Vec<_>>();
+    // Stable sort, so sections with as many mismatches stay in chart order
+    sections.sort_by_key(|section| std::cmp::Reverse(section.mismatches));
+    sections.truncate(WORST_SECTIONS);
+
+    Ok(Evaluation {
+        accuracy,
+        worst_sections: sections,
+    })
+}
+
+/// Steps of an annotated footing, the foot parts placed on the notes of a row are stepping.
+pub(crate) fn annotated_steps(
+    dance_stage: &DanceStage,
+    rows: &[(f64, Row)],
+    annotated: &[FootPlacement],
+) -> Result<Vec<StepEntry>, Error> {
+    let column_count = dance_stage.column_count();
+    let mut states = Vec::with_capacity(rows.len());
+    for (row_index, ((_, row), final_columns)) in rows.iter().zip(annotated).enumerate() {
+        if final_columns.0.len() != column_count {
+            return Err(Error::ColumnCount {
+                expected: column_count,
+                actual: final_columns.0.len(),
+            });
+        }
+        let final_columns = CompactPlacement::try_from(final_columns)?;
+
+        let mut activated_columns = CompactPlacement::new(column_count);
+        for (column, note) in row.columns.iter().enumerate() {
+            if note.require_press() {
+                if final_columns.at(column) == FootPart::None {
+                    return Err(Error::UnsteppedNote { row_index });
+                }
+                activated_columns.set(column, final_columns.at(column));
+            }
+        }
+
+        let state = State {
+            activated_columns,
+            final_columns,
+        };
+        states.push((row_index, state, 0.0));
+    }
+
+    Ok(build_steps(dance_stage, rows, states))
+}
+
+/// Tech performed by only one of the steps, paired with the tech performed by the other one.
+fn confused_tech(expected: &StepEntry, predicted: &StepEntry) -> Vec<(Option<Tech>, Option<Tech>)> {
+    let only_in = |a: &StepEntry, b: &StepEntry| {
+        a.tech
+            .iter()
+            .filter(|&tech| !b.tech.contains(tech))
+            .map(Some)
+            .collect::<Vec<_>>()
+    };
+    let mut expected_only = only_in(expected, predicted);
+    let mut predicted_only = only_in(predicted, expected);
+    if expected_only.is_empty() && predicted_only.is_empty() {
+        return Vec::new();
+    }
+    if expected_only.is_empty() {
+        expected_only.push(None);
+    }
+    if predicted_only.is_empty() {
+        predicted_only.push(None);
+    }
+
+    expected_only
+        .iter()
+        .flat_map(|&e| predicted_only.iter().map(move |&p| (e, p)))
+        .collect()
+}
+
+impl Predictor {
+    /// Predict the footing of a chart, and compare it to its annotated footing.
+    pub fn evaluate(
+        &self,
+        chart: &SMChart,
+        notes_data: &NotesData,
+        annotated: &[FootPlacement],
+    ) -> Result<Evaluation, Error> {
+        let stage = self.stage_for(&notes_data.style)?;
+        let prediction = self.predict(chart, notes_data)?;
+        evaluate(
+            &stage,
+            NoteViewer::new(chart, notes_data),
+            annotated,
+            &prediction.steps,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Note;
+
+    fn rows() -> Vec<(f64, Vec<Note>)> {
+        use Note::*;
+        vec![
+            (0.0, vec![Tap, Empty, Empty, Empty]),
+            (0.5, vec![Empty, Empty, Empty, Tap]),
+            (1.0, vec![Tap, Tap, Empty, Empty]),
+            (1.5, vec![Empty, Mine, Empty, Empty]),
+            (2.0, vec![Empty, Tap, Empty, Empty]),
+        ]
+    }
+
+    fn footing(placements: &[&str]) -> Vec<FootPlacement> {
+        placements
+            .iter()
+            .map(|p| FootPlacement::parse(p).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_perfect_prediction() {
+        let stage = DanceStage::ddr_solo();
+        let predicted = Predictor::default().predict_rows(rows()).unwrap().steps;
+        let annotated = predicted
+            .iter()
+            .map(|step| step.columns.clone())
+            .collect::<Vec<_>>();
+
+        let evaluation = evaluate(&stage, rows(), &annotated, &predicted).unwrap();
+        assert_eq!(evaluation.accuracy.rows, 4);
+        assert_eq!(evaluation.accuracy.row_accuracy(), 1.0);
+        assert_eq!(evaluation.accuracy.foot_accuracy(), 1.0);
+        assert!(evaluation.accuracy.confusions.is_empty());
+        assert!(evaluation.worst_sections.is_empty());
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let stage = DanceStage::ddr_solo();
+        let predicted = Predictor::default().predict_rows(rows()).unwrap().steps;
+        assert_eq!(predicted[2].activated_columns.to_string(), "LR--");
+
+        // Bracket instead of the jump, then a jack with the left toe
+        let annotated = footing(&["L---", "L--R", "Ll-R", "Ll-R", "Ll-R"]);
+        let evaluation = evaluate(&stage, rows(), &annotated, &predicted).unwrap();
+        let accuracy = &evaluation.accuracy;
+        assert_eq!((accuracy.matching_rows, accuracy.rows), (2, 4));
+        assert_eq!((accuracy.matching_feet, accuracy.feet), (2, 6));
+        let confusions = accuracy
+            .confusions
+            .iter()
+            .map(|c| (c.expected, c.predicted, c.count))
+            .collect::<Vec<_>>();
+        assert!(confusions.contains(&(Some(Tech::Bracket), Some(Tech::Jump), 1)));
+        assert_eq!(
+            evaluation.worst_sections,
+            vec![Section {
+                rows: 0..5,
+                start_time: 0.0,
+                end_time: 2.0,
+                mismatches: 2
+            }]
+        );
+
+        assert_eq!(
+            evaluate(&stage, rows(), &annotated[..4], &predicted),
+            Err(Error::RowCount {
+                expected: 5,
+                actual: 4
+            })
+        );
+        let unstepped = footing(&["L---", "L---", "Ll-R", "Ll-R", "Ll-R"]);
+        assert_eq!(
+            evaluate(&stage, rows(), &unstepped, &predicted),
+            Err(Error::UnsteppedNote { row_index: 1 })
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Structured export of step results, for use outside of Rust.
+use crate::graph::StepEntry;
+use std::io::{self, Write};
+
+/// Write the steps as a JSON array of [`StepEntry`] objects.
+pub fn write_json<W: Write>(writer: W, steps: &[StepEntry]) -> io::Result<()> {
+    serde_json::to_writer_pretty(writer, steps).map_err(io::Error::from)
+}
+
+/// Write the steps as CSV, with a header row.
+///
+/// Foot placements use the [`crate::FootPlacement`] text format,
+/// and tech tags are separated by `|`.
+pub fn write_csv<W: Write>(mut writer: W, steps: &[StepEntry]) -> io::Result<()> {
+    writeln!(writer, "time,row_index,activated_columns,columns,cost,tech")?;
+    for step in steps {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{}",
+            step.time, step.row_index, step.activated_columns, step.columns, step.cost, step.tech
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FootPlacement, Tech, TechTags};
+    use ordered_float::OrderedFloat;
+
+    fn steps() -> Vec<StepEntry> {
+        vec![
+            StepEntry {
+                time: OrderedFloat(0.5),
+                row_index: 0,
+                activated_columns: FootPlacement::parse("L--R").unwrap(),
+                columns: FootPlacement::parse("L--R").unwrap(),
+                tech: TechTags::from_iter([Tech::Jump]),
+                cost: OrderedFloat(0.0),
+            },
+            StepEntry {
+                time: OrderedFloat(1.0),
+                row_index: 1,
+                activated_columns: FootPlacement::parse("-L--").unwrap(),
+                columns: FootPlacement::parse("-L-R").unwrap(),
+                tech: TechTags::from_iter([Tech::HoldStep, Tech::Candle]),
+                cost: OrderedFloat(6.0),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut json = Vec::new();
+        write_json(&mut json, &steps()).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value[1]["activated_columns"], "-L--");
+        assert_eq!(value[1]["tech"], serde_json::json!(["candle", "hold-step"]));
+
+        let parsed: Vec<StepEntry> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed, steps());
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &steps()).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "time,row_index,activated_columns,columns,cost,tech\n\
+             0.5,0,L--R,L--R,0,jump\n\
+             1,1,-L--,-L-R,6,candle|hold-step\n"
+        );
+    }
+}
@@ -1,9 +1,11 @@
+use crate::error::Error;
 use crate::extensions::HasPressRequirement;
 use crate::stage::DanceStage;
 use danceparser::Row;
 use std::fmt::{Debug, Display, Formatter};
 
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Side {
     Left,
     Right,
@@ -125,6 +127,119 @@ impl FootPlacement {
         self.0.iter().position(|&x| x == part)
     }
 
+    pub(crate) fn contains(&self, part: FootPart) -> bool {
+        self.0.iter().any(|&x| x == part)
+    }
+
+    /// Amount of columns with a foot part on them.
+    pub(crate) fn part_count(&self) -> usize {
+        self.0.iter().filter(|&&x| x != FootPart::None).count()
+    }
+}
+
+impl Debug for FootPlacement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        <Self as Display>::fmt(self, f)
+    }
+}
+
+impl Display for FootPlacement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for part in &self.0 {
+            <FootPart as Display>::fmt(&part, f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FootPlacement {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FootPlacement {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        FootPlacement::parse(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid foot placement `{s}`")))
+    }
+}
+
+/// Column index used for foot parts which aren't on the stage.
+const NO_COLUMN: u8 = u8::MAX;
+
+/// Compact form of [`FootPlacement`] used by the solver's states.
+///
+/// A foot part can only be on a single column at a time, so the placement is stored as the column
+/// of every foot part, which keeps it small and allocation-free.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompactPlacement {
+    column_count: u8,
+    /// Column of every foot part (except [`FootPart::None`]), in declaration order.
+    part_columns: [u8; 4],
+}
+
+impl CompactPlacement {
+    /// Maximum amount of columns a placement can have.
+    pub(crate) const MAX_COLUMNS: usize = NO_COLUMN as usize;
+
+    /// Panics if there are more than [`CompactPlacement::MAX_COLUMNS`] columns,
+    /// which [`DanceStage::try_new`] doesn't allow.
+    pub(crate) fn new(columns: usize) -> Self {
+        assert!(
+            columns <= Self::MAX_COLUMNS,
+            "A foot placement can't have more than {} columns",
+            Self::MAX_COLUMNS
+        );
+        CompactPlacement {
+            column_count: columns as u8,
+            part_columns: [NO_COLUMN; 4],
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn from_ddr_solo(
+        left: FootPart,
+        down: FootPart,
+        up: FootPart,
+        right: FootPart,
+    ) -> Self {
+        Self::try_from(&FootPlacement::from_ddr_solo(left, down, up, right)).unwrap()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn parse(s: &str) -> Self {
+        Self::try_from(&FootPlacement::parse(s).unwrap()).unwrap()
+    }
+
+    /// Amount of columns.
+    pub(crate) fn len(&self) -> usize {
+        self.column_count as usize
+    }
+
+    /// Foot part on every column.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = FootPart> + '_ {
+        (0..self.len()).map(|column| self.at(column))
+    }
+
+    const fn part_slot(part: FootPart) -> Option<usize> {
+        match part {
+            FootPart::None => None,
+            part => Some(part as usize - 1),
+        }
+    }
+
+    pub(crate) fn get_foot_part_index(&self, part: FootPart) -> Option<usize> {
+        let slot = Self::part_slot(part)?;
+        match self.part_columns[slot] {
+            NO_COLUMN => None,
+            column => Some(column as usize),
+        }
+    }
+
     pub(crate) fn get_foot_part_indices(&self) -> FootPartIndices {
         FootPartIndices {
             left_heel: self.get_foot_part_index(FootPart::LeftHeel),
@@ -135,7 +250,18 @@ impl FootPlacement {
     }
 
     pub(crate) fn contains(&self, part: FootPart) -> bool {
-        self.0.iter().any(|&x| x == part)
+        match Self::part_slot(part) {
+            Some(slot) => self.part_columns[slot] != NO_COLUMN,
+            None => self.part_count() < self.len(),
+        }
+    }
+
+    /// Amount of columns with a foot part on them.
+    pub(crate) fn part_count(&self) -> usize {
+        self.part_columns
+            .iter()
+            .filter(|&&column| column != NO_COLUMN)
+            .count()
     }
 
     pub(crate) fn is_bracketing(&self, side: Side) -> bool {
@@ -145,30 +271,84 @@ impl FootPlacement {
         }
     }
 
+    /// Foot part on the column.
     pub(crate) fn at(&self, column_idx: usize) -> FootPart {
-        self.0[column_idx]
+        assert!(
+            column_idx < self.len(),
+            "Column {column_idx} is out of bounds ({} columns)",
+            self.len()
+        );
+        FootPart::all_except_none()
+            .into_iter()
+            .zip(self.part_columns)
+            .find(|&(_, column)| column as usize == column_idx)
+            .map_or(FootPart::None, |(part, _)| part)
     }
 
-    pub(crate) fn at_mut(&mut self, column_idx: usize) -> &mut FootPart {
-        &mut self.0[column_idx]
+    /// Put the foot part on the column, replacing the part that was there.
+    ///
+    /// A foot part can only be on one column, so it's removed from its previous column.
+    pub(crate) fn set(&mut self, column_idx: usize, part: FootPart) {
+        assert!(
+            column_idx < self.len(),
+            "Column {column_idx} is out of bounds ({} columns)",
+            self.len()
+        );
+        for column in &mut self.part_columns {
+            if *column as usize == column_idx {
+                *column = NO_COLUMN;
+            }
+        }
+        if let Some(slot) = Self::part_slot(part) {
+            self.part_columns[slot] = column_idx as u8;
+        }
     }
 }
 
-impl Debug for FootPlacement {
+impl Debug for CompactPlacement {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         <Self as Display>::fmt(self, f)
     }
 }
 
-impl Display for FootPlacement {
+impl Display for CompactPlacement {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for part in &self.0 {
+        for part in self.iter() {
             <FootPart as Display>::fmt(&part, f)?;
         }
         Ok(())
     }
 }
 
+impl TryFrom<&FootPlacement> for CompactPlacement {
+    type Error = Error;
+
+    /// Fails if a foot part is on more than one column, or if there are too many columns.
+    fn try_from(placement: &FootPlacement) -> Result<Self, Error> {
+        if placement.0.len() > Self::MAX_COLUMNS {
+            return Err(Error::TooManyColumns {
+                max: Self::MAX_COLUMNS,
+                actual: placement.0.len(),
+            });
+        }
+
+        let mut compact = CompactPlacement::new(placement.0.len());
+        for (column, &part) in placement.0.iter().enumerate() {
+            if part != FootPart::None && compact.contains(part) {
+                return Err(Error::DuplicateFootPart(part));
+            }
+            compact.set(column, part);
+        }
+        Ok(compact)
+    }
+}
+
+impl From<CompactPlacement> for FootPlacement {
+    fn from(placement: CompactPlacement) -> Self {
+        FootPlacement(placement.iter().collect())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FootPartIndices {
     pub left_heel: Option<usize>,
@@ -177,14 +357,14 @@ pub struct FootPartIndices {
     pub right_toe: Option<usize>,
 }
 
-pub(crate) fn foot_placement_permutations(stage: &DanceStage, row: &Row) -> Vec<FootPlacement> {
+pub(crate) fn foot_placement_permutations(stage: &DanceStage, row: &Row) -> Vec<CompactPlacement> {
     let mut permutations = Vec::new();
 
     permute_foot_placement(
         &mut permutations,
         stage,
         row,
-        &FootPlacement::new(row.columns.len()),
+        &CompactPlacement::new(row.columns.len()),
         0,
     );
 
@@ -192,10 +372,10 @@ pub(crate) fn foot_placement_permutations(stage: &DanceStage, row: &Row) -> Vec<
 }
 
 fn permute_foot_placement(
-    permutations: &mut Vec<FootPlacement>,
+    permutations: &mut Vec<CompactPlacement>,
     stage: &DanceStage,
     row: &Row,
-    current_placement: &FootPlacement,
+    current_placement: &CompactPlacement,
     column: usize,
 ) {
     if column >= row.columns.len() {
@@ -218,27 +398,27 @@ fn permute_foot_placement(
             return;
         }
 
-        permutations.push(current_placement.clone());
+        permutations.push(*current_placement);
         return;
     }
 
     if let Some(note) = row.columns.get(column)
         && note.require_press()
     {
-        let mut new_placement = current_placement.clone();
+        let mut new_placement = *current_placement;
         for foot_part in FootPart::all_except_none() {
             if current_placement.contains(foot_part) {
                 continue;
             }
 
-            new_placement.0[column] = foot_part;
+            new_placement.set(column, foot_part);
             permute_foot_placement(permutations, stage, row, &new_placement, column + 1);
         }
 
         return;
     }
 
-    permute_foot_placement(permutations, stage, row, &current_placement, column + 1);
+    permute_foot_placement(permutations, stage, row, current_placement, column + 1);
 }
 
 #[cfg(test)]
@@ -246,6 +426,52 @@ mod tests {
     use super::*;
     use danceparser::NoteKind;
 
+    #[test]
+    fn test_placement_format() {
+        for placement in ["L--R", "-lLR", "----", "Lr-lR---", "L-L-", ""] {
+            assert_eq!(
+                FootPlacement::parse(placement).unwrap().to_string(),
+                placement
+            );
+        }
+        assert_eq!(FootPlacement::parse("L-x-"), None);
+    }
+
+    #[test]
+    fn test_compact_placement() {
+        let placement = FootPlacement::parse("-lLR").unwrap();
+        let compact = CompactPlacement::try_from(&placement).unwrap();
+        assert_eq!(compact.len(), 4);
+        assert_eq!(compact.at(1), FootPart::LeftToe);
+        assert_eq!(compact.get_foot_part_index(FootPart::RightHeel), Some(3));
+        assert_eq!(compact.get_foot_part_index(FootPart::RightToe), None);
+        assert!(compact.contains(FootPart::None));
+        assert!(!CompactPlacement::parse("LlRr").contains(FootPart::None));
+        assert_eq!(FootPlacement::from(compact), placement);
+
+        assert_eq!(
+            CompactPlacement::try_from(&FootPlacement::parse("L-L-").unwrap()),
+            Err(Error::DuplicateFootPart(FootPart::LeftHeel))
+        );
+        assert_eq!(
+            CompactPlacement::try_from(&FootPlacement::new(256)),
+            Err(Error::TooManyColumns {
+                max: 255,
+                actual: 256
+            })
+        );
+    }
+
+    #[test]
+    fn test_compact_placement_set() {
+        let mut placement = CompactPlacement::parse("L--R");
+        placement.set(3, FootPart::LeftHeel);
+        assert_eq!(placement.to_string(), "---L");
+        placement.set(3, FootPart::None);
+        assert_eq!(placement, CompactPlacement::new(4));
+        assert_eq!(std::mem::size_of::<CompactPlacement>(), 5);
+    }
+
     #[test]
     fn test_tap_permutations() {
         let permutations = foot_placement_permutations(
@@ -263,13 +489,13 @@ mod tests {
         assert_eq!(
             permutations,
             vec![
-                FootPlacement::from_ddr_solo(
+                CompactPlacement::from_ddr_solo(
                     FootPart::LeftHeel,
                     FootPart::None,
                     FootPart::None,
                     FootPart::None,
                 ),
-                FootPlacement::from_ddr_solo(
+                CompactPlacement::from_ddr_solo(
                     FootPart::RightHeel,
                     FootPart::None,
                     FootPart::None,
@@ -296,13 +522,13 @@ mod tests {
         assert_eq!(
             permutations,
             vec![
-                FootPlacement::from_ddr_solo(
+                CompactPlacement::from_ddr_solo(
                     FootPart::LeftHeel,
                     FootPart::None,
                     FootPart::None,
                     FootPart::RightHeel,
                 ),
-                FootPlacement::from_ddr_solo(
+                CompactPlacement::from_ddr_solo(
                     FootPart::RightHeel,
                     FootPart::None,
                     FootPart::None,
@@ -329,37 +555,37 @@ mod tests {
         assert_eq!(
             permutations,
             vec![
-                FootPlacement::from_ddr_solo(
+                CompactPlacement::from_ddr_solo(
                     FootPart::LeftHeel,
                     FootPart::LeftToe,
                     FootPart::None,
                     FootPart::None
                 ),
-                FootPlacement::from_ddr_solo(
+                CompactPlacement::from_ddr_solo(
                     FootPart::LeftHeel,
                     FootPart::RightHeel,
                     FootPart::None,
                     FootPart::None
                 ),
-                FootPlacement::from_ddr_solo(
+                CompactPlacement::from_ddr_solo(
                     FootPart::LeftToe,
                     FootPart::LeftHeel,
                     FootPart::None,
                     FootPart::None
                 ),
-                FootPlacement::from_ddr_solo(
+                CompactPlacement::from_ddr_solo(
                     FootPart::RightHeel,
                     FootPart::LeftHeel,
                     FootPart::None,
                     FootPart::None
                 ),
-                FootPlacement::from_ddr_solo(
+                CompactPlacement::from_ddr_solo(
                     FootPart::RightHeel,
                     FootPart::RightToe,
                     FootPart::None,
                     FootPart::None
                 ),
-                FootPlacement::from_ddr_solo(
+                CompactPlacement::from_ddr_solo(
                     FootPart::RightToe,
                     FootPart::RightHeel,
                     FootPart::None,
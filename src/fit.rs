@@ -0,0 +1,272 @@
+//! Fitting of cost weights to annotated footings.
+//!
+//! Weights are fitted with a coordinate descent: every weight is in turn scaled up and down,
+//! and the change is kept if more rows match the annotations. Weights span several orders of
+//! magnitude, so they are scaled by a factor rather than shifted by an amount.
+use crate::cost::{CostTerm, CostWeights};
+use crate::error::Error;
+use crate::eval::Accuracy;
+use crate::feet::FootPlacement;
+use crate::parallel::parallel_map;
+use crate::predictor::Predictor;
+use danceparser::{NotesData, SMChart};
+
+/// A difficulty of a chart along with its annotated footing.
+#[derive(Debug, Clone, Copy)]
+pub struct AnnotatedChart<'a> {
+    pub chart: &'a SMChart,
+    pub notes_data: &'a NotesData,
+    pub footing: &'a [FootPlacement],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FitOptions {
+    /// Maximum amount of passes over every weight.
+    pub max_rounds: usize,
+    /// Factor the weights are first scaled by.
+    pub initial_step: f64,
+    /// The fit stops once the factor gets below this.
+    pub min_step: f64,
+    /// Amount of charts evaluated at the same time.
+    pub workers: usize,
+}
+
+impl Default for FitOptions {
+    fn default() -> Self {
+        FitOptions {
+            max_rounds: 10,
+            initial_step: 2.0,
+            min_step: 1.05,
+            workers: 1,
+        }
+    }
+}
+
+/// How much the weight of a term moved during the fit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeightChange {
+    pub term: CostTerm,
+    pub initial: f64,
+    pub fitted: f64,
+}
+
+impl WeightChange {
+    /// Fitted weight relative to the initial one.
+    pub fn ratio(&self) -> f64 {
+        self.fitted / self.initial
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FitReport {
+    pub weights: CostWeights,
+    pub initial_accuracy: Accuracy,
+    pub accuracy: Accuracy,
+    /// Change of every enabled term, in the order of [`CostTerm::ALL`].
+    pub changes: Vec<WeightChange>,
+    /// Amount of passes over every weight.
+    pub rounds: usize,
+    /// Charts left out of the fit, by index in the fitted charts, with the error they failed with.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub failures: Vec<(usize, Error)>,
+}
+
+/// Search the cost weights maximising the amount of rows matching the annotated footings,
+/// starting from the weights of `predictor`.
+///
+/// Disabled terms (with a weight of zero) are left disabled. Charts which can't be evaluated
+/// with the initial weights are left out of the fit and listed in [`FitReport::failures`],
+/// an error is only returned if none of them can be evaluated.
+pub fn fit_weights(
+    predictor: &Predictor,
+    charts: &[AnnotatedChart],
+    options: &FitOptions,
+) -> Result<FitReport, Error> {
+    let evaluate = |weights: CostWeights, charts: &[AnnotatedChart]| {
+        let predictor = predictor.to_builder().cost_weights(weights).build();
+        parallel_map(charts, options.workers, |annotated| {
+            predictor.evaluate(annotated.chart, annotated.notes_data, annotated.footing)
+        })
+    };
+    let score = |accuracy: &Accuracy| (accuracy.matching_rows, accuracy.matching_feet);
+
+    let initial_weights = *predictor.cost_weights();
+    let mut initial_accuracy = Accuracy::default();
+    let mut fitted_charts = Vec::with_capacity(charts.len());
+    let mut failures = Vec::new();
+    for (index, evaluation) in evaluate(initial_weights, charts).into_iter().enumerate() {
+        match evaluation {
+            Ok(evaluation) => {
+                initial_accuracy.add(&evaluation.accuracy);
+                fitted_charts.push(charts[index]);
+            }
+            Err(error) => failures.push((index, error)),
+        }
+    }
+    if fitted_charts.is_empty() && !failures.is_empty() {
+        return Err(failures.swap_remove(0).1);
+    }
+
+    // Same charts as the initial evaluation, a candidate failing on one of them is rejected
+    let candidate_accuracy = |weights: CostWeights| -> Option<Accuracy> {
+        let mut accuracy = Accuracy::default();
+        for evaluation in evaluate(weights, &fitted_charts) {
+            accuracy.add(&evaluation.ok()?.accuracy);
+        }
+        Some(accuracy)
+    };
+
+    let mut weights = initial_weights;
+    let mut accuracy = initial_accuracy.clone();
+
+    let mut step = options.initial_step;
+    let mut rounds = 0;
+    while rounds < options.max_rounds && step >= options.min_step {
+        rounds += 1;
+
+        let mut improved = false;
+        for term in CostTerm::ALL {
+            if weights.get(term) == 0.0 {
+                continue;
+            }
+
+            for factor in [step, 1.0 / step] {
+                let mut candidate = weights;
+                *candidate.get_mut(term) *= factor;
+
+                let Some(candidate_accuracy) = candidate_accuracy(candidate) else {
+                    continue;
+                };
+                if score(&candidate_accuracy) > score(&accuracy) {
+                    weights = candidate;
+                    accuracy = candidate_accuracy;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+
+        if !improved {
+            step = step.sqrt();
+        }
+    }
+
+    let changes = CostTerm::ALL
+        .into_iter()
+        .filter(|&term| initial_weights.get(term) != 0.0)
+        .map(|term| WeightChange {
+            term,
+            initial: initial_weights.get(term),
+            fitted: weights.get(term),
+        })
+        .collect();
+
+    Ok(FitReport {
+        weights,
+        initial_accuracy,
+        accuracy,
+        changes,
+        rounds,
+        failures,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_fit_recovers_weight() {
+        let chart = SMChart::from_sm(Cursor::new(include_str!("../Eientewi Set 12A.sm")))
+            .expect("Failed to parse StepMania chart");
+        let notes_data = &chart.notes[1];
+
+        let target = Predictor::builder()
+            .weight(CostTerm::Facing, CostWeights::default().facing / 10.0)
+            .build();
+        let footing = target
+            .predict(&chart, notes_data)
+            .unwrap()
+            .steps
+            .iter()
+            .map(|step| step.columns.clone())
+            .collect::<Vec<_>>();
+
+        let annotated = AnnotatedChart {
+            chart: &chart,
+            notes_data,
+            footing: &footing,
+        };
+        let options = FitOptions {
+            max_rounds: 1,
+            initial_step: 10.0,
+            ..FitOptions::default()
+        };
+        let report = fit_weights(&Predictor::default(), &[annotated], &options).unwrap();
+
+        assert!(report.initial_accuracy.matching_rows < report.initial_accuracy.rows);
+        assert_eq!(report.accuracy.matching_rows, report.accuracy.rows);
+        assert_eq!(report.rounds, 1);
+        let facing = report
+            .changes
+            .iter()
+            .find(|change| change.term == CostTerm::Facing)
+            .unwrap();
+        assert!(facing.ratio() < 1.0, "{facing:?}");
+    }
+
+    #[test]
+    fn test_fit_skips_failing_charts() {
+        let chart = SMChart::from_sm(Cursor::new(include_str!("../Eientewi Set 12A.sm")))
+            .expect("Failed to parse StepMania chart");
+        let notes_data = chart.notes.last().unwrap();
+
+        let predictor = Predictor::default();
+        let footing = predictor
+            .predict(&chart, notes_data)
+            .unwrap()
+            .steps
+            .iter()
+            .map(|step| step.columns.clone())
+            .collect::<Vec<_>>();
+        let annotated = AnnotatedChart {
+            chart: &chart,
+            notes_data,
+            footing: &footing,
+        };
+        let truncated = AnnotatedChart {
+            footing: &footing[1..],
+            ..annotated
+        };
+
+        let options = FitOptions {
+            max_rounds: 1,
+            ..FitOptions::default()
+        };
+        let report = fit_weights(&predictor, &[truncated, annotated], &options).unwrap();
+        assert_eq!(
+            report.failures,
+            [(
+                0,
+                Error::RowCount {
+                    expected: footing.len(),
+                    actual: footing.len() - 1
+                }
+            )]
+        );
+        assert_eq!(
+            report.initial_accuracy.matching_rows,
+            report.initial_accuracy.rows
+        );
+        assert!(report.initial_accuracy.rows > 0);
+
+        // Nothing to fit on
+        assert_eq!(
+            fit_weights(&predictor, &[truncated], &options),
+            Err(report.failures[0].1.clone())
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use crate::graph::StepGraph;
+use petgraph::Direction;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+/// Amount of states kept per row once the budget is exceeded.
+const FALLBACK_BEAM_WIDTH: usize = 8;
+
+/// Progress of the graph construction, reported after every appended row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Amount of rows appended so far.
+    pub rows: usize,
+    /// Amount of states of the last appended row.
+    pub layer_states: usize,
+    /// Amount of nodes in the whole graph.
+    pub nodes: usize,
+    /// Whether the budget has been exceeded, and the solver switched to a beam search.
+    pub budget_exceeded: bool,
+}
+
+type ProgressCallback = Box<dyn FnMut(&Progress) -> ControlFlow<()> + Send>;
+
+#[derive(Default)]
+pub(crate) struct Budget {
+    deadline: Option<Instant>,
+    callback: Option<ProgressCallback>,
+    exceeded: bool,
+}
+
+impl StepGraph {
+    /// Limit how long the graph construction and solving can take, starting now.
+    ///
+    /// Once the budget is exceeded, only the cheapest states of every new row are kept (beam search),
+    /// so the result is still a complete footing, but not necessarily the cheapest one.
+    ///
+    /// This is a soft bound: the budget is only checked after every appended row and before
+    /// the search, so it can be overrun by the time it takes to append a row or to search the graph.
+    pub fn set_time_budget(&mut self, budget: Duration) {
+        self.budget.deadline = Some(Instant::now() + budget);
+    }
+
+    /// Call `callback` after every appended row.
+    ///
+    /// Returning [`ControlFlow::Break`] cancels the search, with the same effect as exceeding the time budget.
+    pub fn set_progress_callback(
+        &mut self,
+        callback: impl FnMut(&Progress) -> ControlFlow<()> + Send + 'static,
+    ) {
+        self.budget.callback = Some(Box::new(callback));
+    }
+
+    /// Whether the time budget was exceeded or the search was cancelled.
+    pub fn budget_exceeded(&self) -> bool {
+        self.budget.exceeded
+    }
+
+    fn check_deadline(&mut self) {
+        if self
+            .budget
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.budget.exceeded = true;
+        }
+    }
+
+    /// Check the budget after a row has been appended, pruning the new states if it's exceeded.
+    pub(crate) fn after_append(&mut self) {
+        self.check_deadline();
+
+        if let Some(callback) = &mut self.budget.callback {
+            let progress = Progress {
+                rows: self.row_index,
+                layer_states: self.queue.len(),
+                nodes: self.graph.node_count(),
+                budget_exceeded: self.budget.exceeded,
+            };
+            if callback(&progress).is_break() {
+                self.budget.exceeded = true;
+            }
+        }
+
+        if self.budget.exceeded {
+            self.beam_from.get_or_insert(self.row_index - 1);
+            self.prune_queue();
+        }
+    }
+
+    /// Only keep the cheapest states of the last row.
+    pub(crate) fn prune_queue(&mut self) {
+        if self.queue.len() > FALLBACK_BEAM_WIDTH {
+            let distances = &self.distances;
+            self.queue
+                .make_contiguous()
+                .sort_by(|a, b| distances[a.index()].total_cmp(&distances[b.index()]));
+            self.queue.truncate(FALLBACK_BEAM_WIDTH);
+        }
+    }
+
+    /// Whether [`StepGraph::compute_steps`] should skip the full search.
+    pub(crate) fn should_skip_search(&mut self) -> bool {
+        self.check_deadline();
+        self.budget.exceeded
+    }
+
+    /// Cheapest path using the cheapest known cost of every node, beginning with the start node.
+    ///
+    /// Every edge goes from a row to the next one, so these costs are exact,
+    /// this only walks the edges backwards from the cheapest state of the last row.
+    pub(crate) fn cheapest_known_path(&self) -> Option<Vec<NodeIndex>> {
+        let distance = |node: NodeIndex| self.distances[node.index()];
+
+        let mut node = self
+            .queue
+            .iter()
+            .copied()
+            .filter(|&node| distance(node).is_finite())
+            .min_by(|&a, &b| distance(a).total_cmp(&distance(b)))?;
+
+        let mut path = vec![node];
+        while node != self.start_node {
+            node = self
+                .graph
+                .edges_directed(node, Direction::Incoming)
+                .min_by(|a, b| {
+                    (distance(a.source()) + a.weight())
+                        .total_cmp(&(distance(b.source()) + b.weight()))
+                })?
+                .source();
+            path.push(node);
+        }
+
+        path.reverse();
+        Some(path)
+    }
+}
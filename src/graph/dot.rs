@@ -0,0 +1,102 @@
+use crate::graph::StepGraph;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use rustc_hash::FxHashSet;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::ops::RangeBounds;
+
+const PATH_COLOR: &str = "red";
+
+impl StepGraph {
+    /// Export the graph in the Graphviz DOT format.
+    ///
+    /// Nodes are grouped by row, edges are labelled with their cost,
+    /// and the path chosen by [`StepGraph::compute_steps`] is highlighted.
+    /// Only the rows within `rows` are exported, the start node is included if the first row is.
+    pub fn to_dot(&mut self, rows: impl RangeBounds<usize>) -> String {
+        let path = self.best_path().unwrap_or_default();
+        let path_nodes = path.iter().copied().collect::<FxHashSet<_>>();
+        let path_edges = path
+            .windows(2)
+            .filter_map(|window| {
+                self.graph
+                    .edges_connecting(window[0], window[1])
+                    .min_by(|a, b| a.weight().total_cmp(b.weight()))
+                    .map(|edge| edge.id())
+            })
+            .collect::<FxHashSet<_>>();
+
+        let included = |node: NodeIndex| match self.graph[node].row_index {
+            Some(row_index) => rows.contains(&row_index),
+            None => rows.contains(&0),
+        };
+
+        let mut row_nodes = BTreeMap::<Option<usize>, Vec<NodeIndex>>::new();
+        for node in self.graph.node_indices().filter(|&node| included(node)) {
+            row_nodes
+                .entry(self.graph[node].row_index)
+                .or_default()
+                .push(node);
+        }
+
+        let mut dot = String::new();
+        writeln!(dot, "digraph {{").unwrap();
+        writeln!(dot, "    rankdir=LR;").unwrap();
+        writeln!(dot, "    node [shape=box, fontname=monospace];").unwrap();
+
+        for (row_index, nodes) in &row_nodes {
+            let label = match row_index {
+                Some(row_index) => {
+                    format!("Row {row_index} ({:.3}s)", self.graph[nodes[0]].time)
+                }
+                None => "Start".to_string(),
+            };
+            let cluster = row_index.map_or("start".to_string(), |i| i.to_string());
+
+            writeln!(dot, "    subgraph cluster_{cluster} {{").unwrap();
+            writeln!(dot, "        label=\"{label}\";").unwrap();
+            for &node in nodes {
+                let state = &self.graph[node].state;
+                let style = if path_nodes.contains(&node) {
+                    format!(", style=filled, color={PATH_COLOR}, fillcolor=mistyrose")
+                } else {
+                    String::new()
+                };
+                writeln!(
+                    dot,
+                    "        n{} [label=\"{}\\n{}\"{style}];",
+                    node.index(),
+                    state.activated_columns,
+                    state.final_columns
+                )
+                .unwrap();
+            }
+            writeln!(dot, "    }}").unwrap();
+        }
+
+        for edge in self.graph.edge_references() {
+            let (source, target) = (edge.source(), edge.target());
+            if !included(source) || !included(target) {
+                continue;
+            }
+
+            let style = if path_edges.contains(&edge.id()) {
+                format!(", color={PATH_COLOR}, penwidth=2")
+            } else {
+                String::new()
+            };
+            writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{:.2}\"{style}];",
+                source.index(),
+                target.index(),
+                edge.weight()
+            )
+            .unwrap();
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
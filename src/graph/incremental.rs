@@ -0,0 +1,274 @@
+//! Editing the rows of an already built graph.
+use crate::error::Error;
+use crate::graph::{GraphState, StepGraph, rules};
+use danceparser::Row;
+use petgraph::Direction;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+
+/// A removed node, along with its outgoing edges (target, cost) in the order they were added.
+type OldNode = (GraphState, Vec<(usize, f64)>);
+
+/// Layers removed by [`StepGraph::rebuild_from`], kept so they can be put back.
+///
+/// Node indices are relative to the first removed node.
+struct OldLayers {
+    /// First node of every layer, followed by the amount of nodes.
+    starts: Vec<usize>,
+    nodes: Vec<OldNode>,
+    /// Columns held after every layer.
+    held: Vec<Vec<bool>>,
+    /// Layers where the rules had to be broken.
+    violations: Vec<usize>,
+    /// Whether any layer was pruned by the beam search.
+    pruned: bool,
+}
+
+impl OldLayers {
+    fn len(&self) -> usize {
+        self.held.len()
+    }
+
+    fn layer(&self, layer: usize) -> &[OldNode] {
+        &self.nodes[self.starts[layer]..self.starts[layer + 1]]
+    }
+}
+
+impl StepGraph {
+    /// Replace the row at `row_index`.
+    ///
+    /// Only the rows from `row_index` up to the first row whose states are left unchanged by the
+    /// edit are recomputed (and reported to the progress callback), the rest of the graph is kept.
+    pub fn replace_row(&mut self, row_index: usize, time: f64, row: &Row) {
+        if let Err(e) = self.try_replace_row(row_index, time, row) {
+            panic!("{e}");
+        }
+    }
+
+    /// Same as [`StepGraph::replace_row`], but the graph is left unchanged if the row is invalid.
+    pub fn try_replace_row(&mut self, row_index: usize, time: f64, row: &Row) -> Result<(), Error> {
+        assert!(row_index < self.rows.len(), "Row {row_index} doesn't exist");
+        self.check_row(row)?;
+        self.rebuild_from(row_index, |rows| rows[0] = (time, row.clone()));
+        Ok(())
+    }
+
+    /// Insert a row before the row at `row_index`, see [`StepGraph::replace_row`] for which rows are recomputed.
+    pub fn insert_row(&mut self, row_index: usize, time: f64, row: &Row) {
+        if let Err(e) = self.try_insert_row(row_index, time, row) {
+            panic!("{e}");
+        }
+    }
+
+    /// Same as [`StepGraph::insert_row`], but the graph is left unchanged if the row is invalid.
+    pub fn try_insert_row(&mut self, row_index: usize, time: f64, row: &Row) -> Result<(), Error> {
+        assert!(
+            row_index <= self.rows.len(),
+            "Row {row_index} doesn't exist"
+        );
+        self.check_row(row)?;
+        self.rebuild_from(row_index, |rows| rows.insert(0, (time, row.clone())));
+        Ok(())
+    }
+
+    /// Delete the row at `row_index`, see [`StepGraph::replace_row`] for which rows are recomputed.
+    pub fn delete_row(&mut self, row_index: usize) {
+        assert!(row_index < self.rows.len(), "Row {row_index} doesn't exist");
+        self.rebuild_from(row_index, |rows| {
+            rows.remove(0);
+        });
+    }
+
+    /// Recompute every row, e.g after the costs changed.
+    pub(crate) fn rebuild(&mut self) {
+        let rows = std::mem::take(&mut self.rows);
+        self.truncate_layers(0);
+        for (time, row) in &rows {
+            self.append(*time, row);
+        }
+    }
+
+    /// Remove every row starting from `row_index`, edit them, and append them again.
+    ///
+    /// The states of a row depend on every previous row, so the rows after the edit are appended
+    /// again until one of them ends up with the same states and held columns as before the edit.
+    /// From there on the graph is the same as before, so the old layers are put back as they were.
+    fn rebuild_from(&mut self, row_index: usize, edit: impl FnOnce(&mut Vec<(f64, Row)>)) {
+        let old_rows = self.rows.split_off(row_index);
+        let mut rows = old_rows.clone();
+        edit(&mut rows);
+
+        let old = self.old_layers(row_index, &old_rows);
+        self.truncate_layers(row_index);
+
+        // Only the rows at the end which weren't edited can end up with the same states
+        let unedited = old_rows
+            .iter()
+            .rev()
+            .zip(rows.iter().rev())
+            .take_while(|(old_row, row)| old_row == row)
+            .count();
+        let first_unedited = rows.len() - unedited;
+        let old_first_unedited = old_rows.len() - unedited;
+
+        for (i, (time, row)) in rows.iter().enumerate() {
+            self.append(*time, row);
+
+            if i >= first_unedited {
+                let old_layer = i - first_unedited + old_first_unedited;
+                if self.matches_old_layer(&old, old_layer) {
+                    self.restore_layers(&old, old_layer, &rows[i + 1..]);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Copy the layers starting from `row_index`, `rows` being the rows of these layers.
+    fn old_layers(&self, row_index: usize, rows: &[(f64, Row)]) -> OldLayers {
+        let first_node = self
+            .row_starts
+            .get(row_index)
+            .map_or(self.graph.node_count(), |node| node.index());
+
+        let mut starts = self.row_starts[row_index..]
+            .iter()
+            .map(|node| node.index() - first_node)
+            .collect::<Vec<_>>();
+        starts.push(self.graph.node_count() - first_node);
+
+        let nodes = (first_node..self.graph.node_count())
+            .map(NodeIndex::new)
+            .map(|node| {
+                let mut edges = self
+                    .graph
+                    .edges_directed(node, Direction::Outgoing)
+                    .map(|edge| (edge.target().index() - first_node, *edge.weight()))
+                    .collect::<Vec<_>>();
+                // Edges are iterated from the most recently added one
+                edges.reverse();
+                (self.graph[node].clone(), edges)
+            })
+            .collect();
+
+        let mut held = rules::held_columns(self.dance_stage.column_count(), &self.rows);
+        let held = rows
+            .iter()
+            .map(|(_, row)| {
+                rules::update_held(&mut held, row);
+                held.clone()
+            })
+            .collect();
+
+        OldLayers {
+            starts,
+            nodes,
+            held,
+            violations: self
+                .violations
+                .iter()
+                .filter(|&&i| i >= row_index)
+                .map(|i| i - row_index)
+                .collect(),
+            pruned: self.beam_from.is_some_and(|i| i < row_index + rows.len()),
+        }
+    }
+
+    /// Remove every layer starting from `row_index`, the rows must already be removed.
+    fn truncate_layers(&mut self, row_index: usize) {
+        if let Some(&first_node) = self.row_starts.get(row_index) {
+            // Nodes are removed from the end so no other node index gets swapped around
+            while self.graph.node_count() > first_node.index() {
+                self.graph
+                    .remove_node(NodeIndex::new(self.graph.node_count() - 1));
+            }
+            self.node_cache
+                .retain(|state, _| state.row_index.is_none_or(|i| i < row_index));
+            self.edge_cache
+                .retain(|&(_, next, _), _| next.index() < first_node.index());
+            self.row_starts.truncate(row_index);
+            self.distances.truncate(first_node.index());
+
+            let prev_nodes = match row_index.checked_sub(1) {
+                Some(prev_row) => self.row_starts[prev_row].index()..first_node.index(),
+                None => self.start_node.index()..first_node.index(),
+            };
+            self.queue = prev_nodes.map(NodeIndex::new).collect();
+
+            // Only extend the states the beam search kept, like when the row was appended
+            if self.beam_from.is_some_and(|i| i < row_index) {
+                self.prune_queue();
+            }
+        }
+        if self.beam_from.is_some_and(|i| i >= row_index) {
+            self.beam_from = None;
+        }
+        self.row_index = row_index;
+        self.held = rules::held_columns(self.dance_stage.column_count(), &self.rows);
+        self.violations.retain(|&i| i < row_index);
+    }
+
+    /// Whether the last row has the same states as `layer` had before the edit, in the same order,
+    /// so the following layers can be put back.
+    fn matches_old_layer(&self, old: &OldLayers, layer: usize) -> bool {
+        if old.pruned || self.beam_from.is_some() || layer + 1 >= old.len() {
+            return false;
+        }
+
+        let first_node = self.row_starts[self.row_index - 1].index();
+        let states = (first_node..self.graph.node_count()).map(|i| {
+            let node = &self.graph[NodeIndex::new(i)];
+            (&node.state, &node.last_step)
+        });
+        self.held == old.held[layer]
+            && states.eq(old
+                .layer(layer)
+                .iter()
+                .map(|(node, _)| (&node.state, &node.last_step)))
+    }
+
+    /// Put back the layers after `layer`, which has the same states as the last row,
+    /// `rows` being the rows of these layers.
+    fn restore_layers(&mut self, old: &OldLayers, layer: usize, rows: &[(f64, Row)]) {
+        let first_node = self.row_starts[self.row_index - 1].index();
+        let node_index =
+            |old_node: usize| NodeIndex::new(old_node - old.starts[layer] + first_node);
+        let first_row = self.row_index;
+
+        for (old_layer, (time, row)) in (layer + 1..old.len()).zip(rows) {
+            self.row_starts
+                .push(NodeIndex::new(self.graph.node_count()));
+            for (state, _) in old.layer(old_layer) {
+                let state = GraphState {
+                    row_index: Some(self.row_index),
+                    ..state.clone()
+                };
+                let node = self.graph.add_node(state.clone());
+                self.node_cache.insert(state, node);
+                self.distances.push(f64::INFINITY);
+            }
+
+            // In the same order as when the row was appended, so ties are broken the same way
+            for prev in old.starts[old_layer - 1]..old.starts[old_layer] {
+                for &(next, cost) in &old.nodes[prev].1 {
+                    self.add_edge(node_index(prev), node_index(next), cost);
+                }
+            }
+
+            self.rows.push((*time, row.clone()));
+            self.row_index += 1;
+        }
+
+        let last_row = self.row_starts[self.row_index - 1].index();
+        self.queue = (last_row..self.graph.node_count())
+            .map(NodeIndex::new)
+            .collect();
+        self.held = old.held[old.len() - 1].clone();
+        self.violations.extend(
+            old.violations
+                .iter()
+                .filter(|&&i| i > layer)
+                .map(|i| i - layer - 1 + first_row),
+        );
+    }
+}
@@ -1,10 +1,24 @@
 #[cfg(test)]
-mod tests;
+pub(crate) mod tests;
 
-use crate::cost::total_cost;
-use crate::feet::{FootPlacement, foot_placement_permutations};
+mod budget;
+mod dot;
+mod incremental;
+mod probabilities;
+mod rules;
+pub use budget::Progress;
+pub use probabilities::{PlacementProbability, RowProbabilities, SoftSteps};
+pub use rules::{HoldMode, MineMode, Rules};
+
+use budget::Budget;
+
+use crate::cost::{CostWeights, total_cost};
+use crate::error::Error;
+use crate::extensions::HasPressRequirement;
+use crate::feet::{CompactPlacement, FootPart, FootPlacement, Side, foot_placement_permutations};
 use crate::stage::DanceStage;
 use crate::state::State;
+use crate::tech::{TechTags, classify};
 use danceparser::Row;
 use ordered_float::OrderedFloat;
 use petgraph::algo::astar;
@@ -18,6 +32,8 @@ pub struct GraphState {
     time: OrderedFloat<f64>,
     pub row_index: Option<usize>,
     state: State,
+    /// Last state which stepped on a column, which tech is classified against.
+    last_step: State,
 }
 
 impl Display for GraphState {
@@ -33,32 +49,67 @@ impl GraphState {
             time: OrderedFloat(time),
             row_index,
             state,
+            last_step: state,
+        }
+    }
+
+    /// State reached by stepping `placement` at `row_index` from this state.
+    fn next(&self, row_index: usize, time: f64, placement: &CompactPlacement) -> Self {
+        let state = self.state.append(placement);
+        let stepped = state.side_activated(Side::Left) || state.side_activated(Side::Right);
+        GraphState {
+            time: OrderedFloat(time),
+            row_index: Some(row_index),
+            state,
+            last_step: if stepped { state } else { self.last_step },
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StepEntry {
     pub time: OrderedFloat<f64>,
     pub row_index: usize,
     pub activated_columns: FootPlacement,
     pub columns: FootPlacement,
+    pub tech: TechTags,
+    /// Cost of the transition from the previous row to this one.
+    pub cost: OrderedFloat<f64>,
 }
 
 pub struct StepGraph {
     dance_stage: DanceStage,
     row_index: usize,
+    rows: Vec<(f64, Row)>,
+    /// First node of every row, the nodes of a row are always contiguous.
+    row_starts: Vec<NodeIndex>,
 
     queue: VecDeque<NodeIndex>,
     pub graph: DiGraph<GraphState, f64>,
     node_cache: HashMap<GraphState, NodeIndex, FxBuildHasher>,
+    /// Foot placement permutations for every press mask (which columns must be stepped on).
+    permutation_cache: HashMap<Vec<bool>, Vec<CompactPlacement>, FxBuildHasher>,
+    press_mask: Vec<bool>,
     edge_cache: HashMap<(NodeIndex, NodeIndex, OrderedFloat<f64>), EdgeIndex, FxBuildHasher>,
+    /// Cost of the cheapest path from the start node to every node.
+    distances: Vec<f64>,
+    budget: Budget,
+    weights: CostWeights,
+    rules: Rules,
+    /// Columns held after the last appended row.
+    held: Vec<bool>,
+    /// Rows where the rules had to be broken.
+    violations: Vec<usize>,
+    /// First row whose states were pruned by the beam search.
+    beam_from: Option<usize>,
 
     start_node: NodeIndex,
 }
 
 impl StepGraph {
     pub fn new(dance_stage: DanceStage) -> Self {
+        let column_count = dance_stage.column_count();
         let mut graph = DiGraph::new();
         let mut state_map = HashMap::default();
 
@@ -76,59 +127,109 @@ impl StepGraph {
         StepGraph {
             dance_stage,
             row_index: 0,
+            rows: Vec::new(),
+            row_starts: Vec::new(),
 
             queue,
             graph,
             node_cache: state_map,
             edge_cache: HashMap::default(),
+            permutation_cache: HashMap::default(),
+            press_mask: Vec::new(),
+            distances: vec![0.0],
+            budget: Budget::default(),
+            weights: CostWeights::default(),
+            rules: Rules::default(),
+            held: vec![false; column_count],
+            violations: Vec::new(),
+            beam_from: None,
 
             start_node,
         }
     }
 
     pub fn append(&mut self, time: f64, row: &Row) {
-        assert_eq!(
-            row.columns.len(),
-            self.dance_stage.column_count(),
-            "Got row ({:?}), expected {} columns",
-            row.columns,
-            self.dance_stage.column_count()
-        );
+        if let Err(e) = self.try_append(time, row) {
+            panic!("{e}");
+        }
+    }
+
+    /// Same as [`StepGraph::append`], but the graph is left unchanged if the row is invalid.
+    pub fn try_append(&mut self, time: f64, row: &Row) -> Result<(), Error> {
+        self.check_row(row)?;
 
-        let permutations = foot_placement_permutations(&self.dance_stage, row);
-
-        let mut new_states = Vec::new();
-        while let Some(prev) = self.queue.pop_front() {
-            for permutation in &permutations {
-                let next_state = GraphState::new(
-                    Some(self.row_index),
-                    time,
-                    self.graph[prev].state.append(permutation),
-                );
-                let prev_state = &self.graph[prev];
-                let cost = total_cost(
-                    &self.dance_stage,
-                    row,
-                    &prev_state.state,
-                    &next_state.state,
-                    next_state.time.0 - prev_state.time.0,
-                );
-
-                let next = if let Some(&next) = self.node_cache.get(&next_state) {
-                    next
-                } else {
-                    let next = self.graph.add_node(next_state.clone());
-                    self.node_cache.insert(next_state, next);
-                    new_states.push(next);
-                    next
-                };
-
-                self.add_edge(prev, next, cost);
+        // The permutations only depend on which columns must be pressed
+        self.press_mask.clear();
+        self.press_mask
+            .extend(row.columns.iter().map(|note| note.require_press()));
+        if !self.permutation_cache.contains_key(&self.press_mask) {
+            self.permutation_cache.insert(
+                self.press_mask.clone(),
+                foot_placement_permutations(&self.dance_stage, row),
+            );
+        }
+        let permutations = &self.permutation_cache[&self.press_mask];
+
+        let mut candidates = Vec::with_capacity(self.queue.len() * permutations.len());
+        for &prev in &self.queue {
+            let prev_state = &self.graph[prev];
+            for permutation in permutations {
+                let next_state = prev_state.next(self.row_index, time, permutation);
+                candidates.push((prev, next_state));
             }
         }
+        let followed_rules = self.retain_allowed(row, &mut candidates);
+
+        let weights = self.rules.effective_weights(&self.weights);
+        let mut transitions = Vec::with_capacity(candidates.len());
+        for (prev, next_state) in candidates {
+            let prev_state = &self.graph[prev];
+            let cost = total_cost(
+                &weights,
+                &self.dance_stage,
+                row,
+                &prev_state.state,
+                &next_state.state,
+                next_state.time.0 - prev_state.time.0,
+            )?;
+            transitions.push((prev, next_state, cost));
+        }
+
+        self.row_starts
+            .push(NodeIndex::new(self.graph.node_count()));
+        self.queue.clear();
+        for (prev, next_state, cost) in transitions {
+            let next = if let Some(&next) = self.node_cache.get(&next_state) {
+                next
+            } else {
+                let next = self.graph.add_node(next_state.clone());
+                self.node_cache.insert(next_state, next);
+                self.queue.push_back(next);
+                next
+            };
 
-        self.queue.extend(new_states);
+            self.add_edge(prev, next, cost);
+        }
+
+        if !followed_rules {
+            self.violations.push(self.row_index);
+        }
+        rules::update_held(&mut self.held, row);
+        self.rows.push((time, row.clone()));
         self.row_index += 1;
+        self.after_append();
+        Ok(())
+    }
+
+    /// Whether the row has as many columns as the stage.
+    fn check_row(&self, row: &Row) -> Result<(), Error> {
+        if row.columns.len() != self.dance_stage.column_count() {
+            return Err(Error::ColumnCount {
+                expected: self.dance_stage.column_count(),
+                actual: row.columns.len(),
+            });
+        }
+        Ok(())
     }
 
     fn add_edge(&mut self, prev: NodeIndex, next: NodeIndex, cost: f64) {
@@ -139,6 +240,14 @@ impl StepGraph {
             let edge = self.graph.add_edge(prev, next, cost);
             self.edge_cache
                 .insert((prev, next, OrderedFloat(cost)), edge);
+
+            let distance = self.distances[prev.index()] + cost;
+            if next.index() == self.distances.len() {
+                self.distances.push(distance);
+            } else {
+                let next_distance = &mut self.distances[next.index()];
+                *next_distance = next_distance.min(distance);
+            }
         }
     }
 
@@ -150,12 +259,32 @@ impl StepGraph {
             .collect()
     }
 
+    /// Compute the cheapest footing, or an empty `Vec` if there is none.
     pub fn compute_steps(&mut self) -> Vec<StepEntry> {
+        self.try_compute_steps().unwrap_or_default()
+    }
+
+    /// Compute the cheapest footing, returning [`Error::NoPath`] if there is none.
+    ///
+    /// If the time budget is exceeded (see [`StepGraph::set_time_budget`]),
+    /// the cheapest path found by the beam search is returned instead.
+    pub fn try_compute_steps(&mut self) -> Result<Vec<StepEntry>, Error> {
+        let path = if self.should_skip_search() {
+            self.cheapest_known_path()
+        } else {
+            self.best_path()
+        };
+        let path = path.ok_or(Error::NoPath)?;
+        Ok(self.path_steps(&path))
+    }
+
+    /// Find the cheapest path through the graph, beginning with the start node.
+    fn best_path(&mut self) -> Option<Vec<NodeIndex>> {
         // Final empty state, just to set as a goal
         let final_state =
             GraphState::new(None, f64::NAN, State::new(self.dance_stage.column_count()));
         let final_node = self.graph.add_node(final_state);
-        while let Some(prev) = self.queue.pop_front() {
+        for &prev in &self.queue {
             self.graph.add_edge(prev, final_node, 0.0);
         }
 
@@ -167,28 +296,67 @@ impl StepGraph {
             |_| 0.0,
         );
 
-        let path = if let Some((_cost, path)) = res {
-            // Ignore empty start and end nodes
-            let path = &path[1..path.len() - 1];
-            path.iter()
-                .map(|node| {
-                    let graph_state = &self.graph[*node];
-                    let state = &graph_state.state;
-                    StepEntry {
-                        time: graph_state.time,
-                        row_index: graph_state.row_index.unwrap(),
-                        activated_columns: state.activated_columns.clone(),
-                        columns: state.final_columns.clone(),
-                    }
-                })
-                .collect()
-        } else {
-            Vec::new()
-        };
-
         // Make the graph re-usable again
         self.graph.remove_node(final_node);
 
-        path
+        res.map(|(_cost, mut path)| {
+            // Ignore the empty end node, the start node is only used for the first step's cost
+            path.pop();
+            path
+        })
     }
+
+    /// Build the step entries for a path, `path` must begin with the start node.
+    fn path_steps(&self, path: &[NodeIndex]) -> Vec<StepEntry> {
+        let states = path.windows(2).map(|window| {
+            let &[prev, node] = window else {
+                unreachable!()
+            };
+            let graph_state = &self.graph[node];
+            let cost = self
+                .graph
+                .edges_connecting(prev, node)
+                .map(|edge| OrderedFloat(*edge.weight()))
+                .min()
+                .unwrap_or_default();
+            (graph_state.row_index.unwrap(), graph_state.state, cost.0)
+        });
+        build_steps(&self.dance_stage, &self.rows, states)
+    }
+}
+
+/// Build the step entries of the state reached at every row, with the cost of reaching it.
+pub(crate) fn build_steps(
+    dance_stage: &DanceStage,
+    rows: &[(f64, Row)],
+    states: impl IntoIterator<Item = (usize, State, f64)>,
+) -> Vec<StepEntry> {
+    let column_count = dance_stage.column_count();
+    let mut held = vec![false; column_count];
+    let mut prev_step = State::new(column_count);
+
+    let mut steps = Vec::with_capacity(rows.len());
+    for (row_index, state, cost) in states {
+        let (time, row) = &rows[row_index];
+
+        let holding = (0..column_count)
+            .any(|column| held[column] && state.activated_columns.at(column) == FootPart::None);
+        let tech = classify(dance_stage, &prev_step, &state, holding);
+
+        rules::update_held(&mut held, row);
+        if state.side_activated(Side::Left) || state.side_activated(Side::Right) {
+            prev_step = state;
+        }
+
+        steps.push(StepEntry {
+            time: OrderedFloat(*time),
+            row_index,
+            activated_columns: state.activated_columns.into(),
+            columns: state.final_columns.into(),
+            tech,
+            cost: OrderedFloat(cost),
+        });
+    }
+
+    steps
 }
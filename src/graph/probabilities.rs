@@ -0,0 +1,161 @@
+use crate::error::Error;
+use crate::feet::FootPlacement;
+use crate::graph::StepGraph;
+use ordered_float::OrderedFloat;
+use petgraph::Direction;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+
+/// Probability of a single foot placement candidate for a row.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlacementProbability {
+    pub columns: FootPlacement,
+    pub probability: f64,
+}
+
+/// All foot placement candidates for a row, sorted by descending probability.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RowProbabilities {
+    pub time: OrderedFloat<f64>,
+    pub row_index: usize,
+    pub candidates: Vec<PlacementProbability>,
+}
+
+impl RowProbabilities {
+    /// The most likely foot placement for this row.
+    pub fn best(&self) -> Option<&PlacementProbability> {
+        self.candidates.first()
+    }
+
+    /// Probability of the most likely foot placement for this row.
+    pub fn confidence(&self) -> f64 {
+        self.best().map_or(0.0, |c| c.probability)
+    }
+}
+
+/// Result of the probabilistic (forward-backward) pass over a [`StepGraph`].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SoftSteps {
+    pub rows: Vec<RowProbabilities>,
+    /// Average of the per-row confidences, in the range `0.0..=1.0`.
+    pub confidence: f64,
+}
+
+fn log_add_exp(a: f64, b: f64) -> f64 {
+    if a == f64::NEG_INFINITY {
+        return b;
+    }
+    if b == f64::NEG_INFINITY {
+        return a;
+    }
+
+    let max = a.max(b);
+    max + ((a - max).exp() + (b - max).exp()).ln()
+}
+
+impl StepGraph {
+    /// Compute the probability of every foot placement candidate per row.
+    ///
+    /// Edge costs are turned into weights with `exp(-cost / temperature)`,
+    /// a low temperature approaches the hard [`StepGraph::compute_steps`] answer
+    /// while a high temperature spreads the probability over more candidates.
+    ///
+    /// Returns no rows if there is no footing, see [`StepGraph::try_compute_probabilities`].
+    pub fn compute_probabilities(&self, temperature: f64) -> SoftSteps {
+        self.try_compute_probabilities(temperature)
+            .unwrap_or_default()
+    }
+
+    /// Same as [`StepGraph::compute_probabilities`], returning [`Error::NoPath`] if there is no footing.
+    pub fn try_compute_probabilities(&self, temperature: f64) -> Result<SoftSteps, Error> {
+        assert!(temperature > 0.0, "Temperature must be positive");
+
+        let node_count = self.graph.node_count();
+        let Some(last_row) = self.row_index.checked_sub(1) else {
+            return Ok(SoftSteps {
+                rows: Vec::new(),
+                confidence: 0.0,
+            });
+        };
+
+        // Nodes are only ever connected to nodes of the next row,
+        // which are always added after them, so index order is a topological order.
+        let mut log_alpha = vec![f64::NEG_INFINITY; node_count];
+        log_alpha[self.start_node.index()] = 0.0;
+        for node in self.graph.node_indices() {
+            for edge in self.graph.edges_directed(node, Direction::Incoming) {
+                let value = log_alpha[edge.source().index()] - edge.weight() / temperature;
+                log_alpha[node.index()] = log_add_exp(log_alpha[node.index()], value);
+            }
+        }
+
+        let is_last_row = |node: NodeIndex| self.graph[node].row_index == Some(last_row);
+
+        let mut log_beta = vec![f64::NEG_INFINITY; node_count];
+        for node in self.graph.node_indices().rev() {
+            if is_last_row(node) {
+                log_beta[node.index()] = 0.0;
+                continue;
+            }
+
+            for edge in self.graph.edges_directed(node, Direction::Outgoing) {
+                let value = log_beta[edge.target().index()] - edge.weight() / temperature;
+                log_beta[node.index()] = log_add_exp(log_beta[node.index()], value);
+            }
+        }
+
+        let log_z = self
+            .graph
+            .node_indices()
+            .filter(|&node| is_last_row(node))
+            .fold(f64::NEG_INFINITY, |acc, node| {
+                log_add_exp(acc, log_alpha[node.index()])
+            });
+        // No state of the last row can be reached
+        if !log_z.is_finite() {
+            return Err(Error::NoPath);
+        }
+
+        let mut rows = (0..=last_row)
+            .map(|row_index| RowProbabilities {
+                time: OrderedFloat(f64::NAN),
+                row_index,
+                candidates: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        for node in self.graph.node_indices() {
+            let graph_state = &self.graph[node];
+            let Some(row_index) = graph_state.row_index else {
+                continue;
+            };
+
+            let probability = (log_alpha[node.index()] + log_beta[node.index()] - log_z).exp();
+            let row = &mut rows[row_index];
+            row.time = graph_state.time;
+
+            let columns = FootPlacement::from(graph_state.state.final_columns);
+            if let Some(candidate) = row.candidates.iter_mut().find(|c| c.columns == columns) {
+                candidate.probability += probability;
+            } else {
+                row.candidates.push(PlacementProbability {
+                    columns,
+                    probability,
+                });
+            }
+        }
+
+        for row in &mut rows {
+            row.candidates
+                .sort_by(|a, b| b.probability.total_cmp(&a.probability));
+        }
+
+        let confidence =
+            rows.iter().map(RowProbabilities::confidence).sum::<f64>() / rows.len() as f64;
+
+        Ok(SoftSteps { rows, confidence })
+    }
+}
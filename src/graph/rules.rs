@@ -0,0 +1,166 @@
+use crate::cost::{CostTerm, CostWeights};
+use crate::feet::FootPart;
+use crate::graph::{GraphState, StepGraph};
+use crate::stage::DanceStage;
+use crate::tech::{Tech, TechTags, classify};
+use danceparser::{NoteKind, Row};
+use petgraph::graph::NodeIndex;
+
+/// How holds constrain the feet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HoldMode {
+    /// Hold heads are stepped like taps, the foot is free to leave before the tail.
+    #[default]
+    Free,
+    /// The foot stepping a hold head stays on it until the tail.
+    Sustain,
+}
+
+/// How mines constrain the feet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MineMode {
+    /// Feet left on mines are penalized by [`CostTerm::Mine`].
+    #[default]
+    Avoid,
+    /// Feet are never left on mines, unless the chart leaves no other choice.
+    Forbid,
+    /// Mines are ignored.
+    Ignore,
+}
+
+/// Hard rules restricting which states the search considers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rules {
+    pub hold: HoldMode,
+    pub mine: MineMode,
+    /// Tech never performed, unless the chart leaves no other choice.
+    pub forbidden: TechTags,
+}
+
+impl Rules {
+    /// Weights actually used for the costs under these rules.
+    pub(crate) fn effective_weights(&self, weights: &CostWeights) -> CostWeights {
+        match self.mine {
+            MineMode::Avoid => *weights,
+            MineMode::Forbid | MineMode::Ignore => weights.without(CostTerm::Mine),
+        }
+    }
+
+    /// Never perform `tech`, unless the chart leaves no other choice.
+    pub fn forbid(mut self, tech: Tech) -> Self {
+        self.forbidden.insert(tech);
+        self
+    }
+
+    fn allows(
+        &self,
+        stage: &DanceStage,
+        row: &Row,
+        held: &[bool],
+        prev: &GraphState,
+        next: &GraphState,
+    ) -> bool {
+        let (prev_step, prev, next) = (&prev.last_step, &prev.state, &next.state);
+        for (column, note) in row.columns.iter().enumerate() {
+            if self.hold == HoldMode::Sustain
+                && held[column]
+                && *note != NoteKind::Tail
+                && prev.final_columns.at(column) != FootPart::None
+                && next.final_columns.at(column) != prev.final_columns.at(column)
+            {
+                return false;
+            }
+
+            if self.mine == MineMode::Forbid
+                && *note == NoteKind::Mine
+                && next.final_columns.at(column) != FootPart::None
+            {
+                return false;
+            }
+        }
+
+        if !self.forbidden.is_empty() {
+            let holding = (0..held.len())
+                .any(|column| held[column] && next.activated_columns.at(column) == FootPart::None);
+            let tech = classify(stage, prev_step, next, holding);
+            if tech.iter().any(|tech| self.forbidden.contains(tech)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Columns still held after `rows`.
+pub(crate) fn held_columns(column_count: usize, rows: &[(f64, Row)]) -> Vec<bool> {
+    let mut held = vec![false; column_count];
+    for (_, row) in rows {
+        update_held(&mut held, row);
+    }
+    held
+}
+
+pub(crate) fn update_held(held: &mut [bool], row: &Row) {
+    for (column, note) in row.columns.iter().enumerate() {
+        match note {
+            NoteKind::HoldHead | NoteKind::RollHead => held[column] = true,
+            NoteKind::Tail => held[column] = false,
+            _ => {}
+        }
+    }
+}
+
+impl StepGraph {
+    /// Set the weights of the cost terms, the graph is rebuilt if rows were already appended.
+    pub fn set_cost_weights(&mut self, weights: CostWeights) {
+        if self.weights != weights {
+            self.weights = weights;
+            self.rebuild();
+        }
+    }
+
+    /// Set the rules of the search, the graph is rebuilt if rows were already appended.
+    pub fn set_rules(&mut self, rules: Rules) {
+        if self.rules != rules {
+            self.rules = rules;
+            self.rebuild();
+        }
+    }
+
+    pub fn cost_weights(&self) -> &CostWeights {
+        &self.weights
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Rows where the rules had to be broken, as the chart left no other choice.
+    pub fn rule_violations(&self) -> &[usize] {
+        &self.violations
+    }
+
+    /// Only keep the transitions allowed by the rules.
+    ///
+    /// If the rules would remove every transition, the chart leaves no other choice,
+    /// so they are all kept and `false` is returned.
+    pub(crate) fn retain_allowed(
+        &self,
+        row: &Row,
+        transitions: &mut Vec<(NodeIndex, GraphState)>,
+    ) -> bool {
+        let allowed = |(prev, next): &(NodeIndex, GraphState)| {
+            self.rules
+                .allows(&self.dance_stage, row, &self.held, &self.graph[*prev], next)
+        };
+        if transitions.iter().any(allowed) {
+            transitions.retain(allowed);
+            true
+        } else {
+            transitions.is_empty()
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{DanceStage, Progress, StepEntry, StepGraph};
+use approx::assert_relative_eq;
+use danceparser::SMChart;
+use danceparser::view::{NoteView, NoteViewer};
+use std::io::Cursor;
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn chart() -> SMChart {
+    SMChart::from_sm(Cursor::new(include_str!("../../../Eientewi Set 12A.sm")))
+        .expect("Failed to parse StepMania chart")
+}
+
+fn total_cost(steps: &[StepEntry]) -> f64 {
+    steps.iter().map(|step| step.cost.0).sum()
+}
+
+fn append_all(graph: &mut StepGraph, chart: &SMChart) -> usize {
+    let mut rows = 0;
+    for NoteView { time, row } in NoteViewer::new(chart, &chart.notes[0]) {
+        graph.append(time, row);
+        rows += 1;
+    }
+    rows
+}
+
+#[test]
+fn progress_callback() {
+    let chart = chart();
+    let reports = Arc::new(Mutex::new(Vec::<Progress>::new()));
+
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    let callback_reports = reports.clone();
+    graph.set_progress_callback(move |progress| {
+        callback_reports.lock().unwrap().push(*progress);
+        ControlFlow::Continue(())
+    });
+    let rows = append_all(&mut graph, &chart);
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), rows);
+    for (i, progress) in reports.iter().enumerate() {
+        assert_eq!(progress.rows, i + 1);
+        assert!(progress.layer_states > 0);
+        assert!(!progress.budget_exceeded);
+    }
+    assert_eq!(reports.last().unwrap().nodes, graph.graph.node_count());
+    assert!(!graph.budget_exceeded());
+}
+
+#[test]
+fn cancelled_search_is_complete() {
+    let chart = chart();
+
+    let mut full = StepGraph::new(DanceStage::ddr_solo());
+    let rows = append_all(&mut full, &chart);
+    let full_steps = full.compute_steps();
+
+    // Cancelling on the last row keeps its cheapest states, so the result is as cheap
+    // (equally cheap footings can be chosen differently)
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    graph.set_progress_callback(move |progress| {
+        if progress.rows == rows {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    append_all(&mut graph, &chart);
+    assert!(graph.budget_exceeded());
+    let steps = graph.compute_steps();
+    assert_eq!(steps.len(), full_steps.len());
+    assert_relative_eq!(total_cost(&steps), total_cost(&full_steps));
+
+    // Cancelling early still gives a footing for every row
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    graph.set_progress_callback(|progress| {
+        if progress.rows >= 10 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    append_all(&mut graph, &chart);
+    let steps = graph.compute_steps();
+    assert_eq!(steps.len(), rows);
+    assert!(graph.graph.node_count() < full.graph.node_count());
+}
+
+#[test]
+fn time_budget() {
+    let chart = chart();
+
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    graph.set_time_budget(Duration::ZERO);
+    let rows = append_all(&mut graph, &chart);
+    assert!(graph.budget_exceeded());
+
+    let steps = graph.try_compute_steps().unwrap();
+    assert_eq!(steps.len(), rows);
+    assert!(
+        steps
+            .iter()
+            .enumerate()
+            .all(|(i, step)| step.row_index == i)
+    );
+}
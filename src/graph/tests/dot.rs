@@ -0,0 +1,30 @@
+use super::tap;
+use crate::{DanceStage, StepGraph};
+
+#[test]
+fn dot_export() {
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    for (i, column) in [0, 3, 1, 2].into_iter().enumerate() {
+        graph.append(i as f64 * 0.5, &tap(column));
+    }
+    let steps = graph.compute_steps();
+
+    let dot = graph.to_dot(..);
+    assert!(dot.starts_with("digraph {"));
+    assert!(dot.contains("subgraph cluster_start"));
+    for row_index in 0..4 {
+        assert!(dot.contains(&format!("subgraph cluster_{row_index} {{")));
+    }
+    // One highlighted edge per row, from the start node to the last row
+    assert_eq!(dot.matches("penwidth=2").count(), steps.len());
+
+    // Exporting doesn't affect the result
+    assert_eq!(graph.compute_steps(), steps);
+
+    let dot = graph.to_dot(1..3);
+    assert!(!dot.contains("cluster_start"));
+    assert!(!dot.contains("cluster_0 "));
+    assert!(!dot.contains("cluster_3 "));
+    assert!(dot.contains("cluster_1 ") && dot.contains("cluster_2 "));
+    assert_eq!(dot.matches("penwidth=2").count(), 1);
+}
@@ -0,0 +1,91 @@
+use super::tap;
+use crate::{DanceStage, Error, SoftSteps, StagePosition, StepGraph};
+use danceparser::{NoteKind, Row};
+
+#[test]
+fn invalid_rows() {
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    graph.append(0.0, &tap(0));
+    assert_eq!(
+        graph.try_append(
+            0.5,
+            &Row {
+                columns: vec![NoteKind::Tap; 5]
+            }
+        ),
+        Err(Error::ColumnCount {
+            expected: 4,
+            actual: 5
+        })
+    );
+
+    // The invalid row didn't change the graph
+    graph.append(1.0, &tap(0));
+    let mut fresh = StepGraph::new(DanceStage::ddr_solo());
+    fresh.append(0.0, &tap(0));
+    fresh.append(1.0, &tap(0));
+    assert_eq!(graph.try_compute_steps(), Ok(fresh.compute_steps()));
+}
+
+#[test]
+fn no_path() {
+    // Five panels can't be stepped on at once with two feet
+    let mut graph = StepGraph::new(DanceStage::pump_single());
+    graph.append(
+        0.0,
+        &Row {
+            columns: vec![NoteKind::Tap; 5],
+        },
+    );
+    assert_eq!(graph.try_compute_steps(), Err(Error::NoPath));
+    assert_eq!(graph.compute_steps(), Vec::new());
+
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    assert_eq!(graph.try_compute_steps(), Ok(Vec::new()));
+}
+
+#[test]
+fn no_path_probabilities() {
+    // The first row can be stepped on, but not the last one
+    let mut graph = StepGraph::new(DanceStage::pump_single());
+    let mut columns = vec![NoteKind::Empty; 5];
+    columns[0] = NoteKind::Tap;
+    graph.append(0.0, &Row { columns });
+    graph.append(
+        1.0,
+        &Row {
+            columns: vec![NoteKind::Tap; 5],
+        },
+    );
+    assert_eq!(graph.try_compute_probabilities(1.0), Err(Error::NoPath));
+    assert_eq!(graph.compute_probabilities(1.0), SoftSteps::default());
+}
+
+#[test]
+fn nan_position() {
+    assert_eq!(
+        StagePosition::try_new(f64::NAN, 0.0),
+        Err(Error::NanPosition)
+    );
+    assert!(StagePosition::try_new(1.0, -1.0).is_ok());
+}
+
+#[test]
+fn invalid_stages() {
+    assert_eq!(
+        DanceStage::try_new(vec![
+            StagePosition::new(0.0, 0.0),
+            StagePosition::new(1.0, 0.0),
+            StagePosition::new(0.0, 0.0),
+        ]),
+        Err(Error::DuplicatePosition {
+            first: 0,
+            second: 2
+        })
+    );
+    assert!(DanceStage::try_new(vec![StagePosition::new(0.0, 0.0)]).is_ok());
+
+    let position = StagePosition::new(0.0, 1.0);
+    assert_eq!(position.checked_div(0.0), Err(Error::NanPosition));
+    assert_eq!(position.checked_div(2.0), Ok(StagePosition::new(0.0, 0.5)));
+}
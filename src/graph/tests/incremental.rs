@@ -0,0 +1,218 @@
+use super::{row, tap};
+use crate::{DanceStage, Error, HoldMode, Rules, StepGraph};
+use danceparser::view::{NoteView, NoteViewer};
+use danceparser::{NoteKind, Row, SMChart};
+use std::io::Cursor;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+fn fresh_graph(rows: &[(f64, Row)]) -> StepGraph {
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    for (time, row) in rows {
+        graph.append(*time, row);
+    }
+    graph
+}
+
+fn fresh_steps(rows: &[(f64, Row)]) -> Vec<crate::StepEntry> {
+    fresh_graph(rows).compute_steps()
+}
+
+#[test]
+fn incremental_edits_match_fresh_solve() {
+    let chart = SMChart::from_sm(Cursor::new(include_str!("../../../Eientewi Set 12A.sm")))
+        .expect("Failed to parse StepMania chart");
+    let mut rows = NoteViewer::new(&chart, &chart.notes[3])
+        .map(|NoteView { time, row }| (time, row.clone()))
+        .collect::<Vec<_>>();
+
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    for (time, row) in &rows {
+        graph.append(*time, row);
+    }
+    assert_eq!(graph.compute_steps(), fresh_steps(&rows));
+
+    let jump = row("1001");
+
+    let time = rows[10].0;
+    graph.replace_row(10, time, &jump);
+    rows[10] = (time, jump.clone());
+    assert_eq!(graph.compute_steps(), fresh_steps(&rows));
+
+    let time = (rows[20].0 + rows[21].0) / 2.0;
+    graph.insert_row(21, time, &jump);
+    rows.insert(21, (time, jump.clone()));
+    assert_eq!(graph.compute_steps(), fresh_steps(&rows));
+
+    graph.delete_row(0);
+    rows.remove(0);
+    assert_eq!(graph.compute_steps(), fresh_steps(&rows));
+
+    let time = rows.last().unwrap().0 + 1.0;
+    graph.insert_row(rows.len(), time, &jump);
+    rows.push((time, jump.clone()));
+    assert_eq!(graph.compute_steps(), fresh_steps(&rows));
+
+    // Appending still works after an edit
+    graph.delete_row(rows.len() - 1);
+    rows.pop();
+    graph.append(time, &jump);
+    rows.push((time, jump));
+    assert_eq!(graph.compute_steps(), fresh_steps(&rows));
+
+    // No stale nodes or edges are left behind
+    let fresh = fresh_graph(&rows);
+    assert_eq!(graph.graph.node_count(), fresh.graph.node_count());
+    assert_eq!(graph.graph.edge_count(), fresh.graph.edge_count());
+}
+
+fn chart_rows() -> Vec<(f64, Row)> {
+    let chart = SMChart::from_sm(Cursor::new(include_str!("../../../Eientewi Set 12A.sm")))
+        .expect("Failed to parse StepMania chart");
+    NoteViewer::new(&chart, &chart.notes[0])
+        .map(|NoteView { time, row }| (time, row.clone()))
+        .collect()
+}
+
+#[test]
+fn middle_edits_only_recompute_nearby_rows() {
+    let mut rows = chart_rows();
+    let mut graph = fresh_graph(&rows);
+
+    let recomputed = Arc::new(AtomicUsize::new(0));
+    let counter = recomputed.clone();
+    graph.set_progress_callback(move |_| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        ControlFlow::Continue(())
+    });
+
+    let middle = rows.len() / 2;
+    let check = |name: &str, graph: &mut StepGraph, rows: &[(f64, Row)]| {
+        // The states converge back within a few rows, the rest of the chart is reused
+        let recomputed = recomputed.swap(0, Ordering::Relaxed);
+        assert!(
+            (1..=8).contains(&recomputed),
+            "{name} recomputed {recomputed} of {} rows",
+            rows.len() - middle
+        );
+
+        let fresh = fresh_graph(rows);
+        assert_eq!(graph.compute_steps(), fresh_steps(rows), "{name}");
+        assert_eq!(graph.graph.node_count(), fresh.graph.node_count(), "{name}");
+        assert_eq!(graph.graph.edge_count(), fresh.graph.edge_count(), "{name}");
+    };
+
+    let time = rows[middle].0;
+    graph.replace_row(middle, time, &tap(0));
+    rows[middle] = (time, tap(0));
+    check("replace", &mut graph, &rows);
+
+    let time = (rows[middle].0 + rows[middle + 1].0) / 2.0;
+    graph.insert_row(middle + 1, time, &tap(3));
+    rows.insert(middle + 1, (time, tap(3)));
+    check("insert", &mut graph, &rows);
+
+    graph.delete_row(middle);
+    rows.remove(middle);
+    check("delete", &mut graph, &rows);
+}
+
+#[test]
+fn edits_under_time_budget_match_fresh_build() {
+    let budget_graph = |rows: &[(f64, Row)]| {
+        let mut graph = StepGraph::new(DanceStage::ddr_solo());
+        graph.set_time_budget(Duration::ZERO);
+        for (time, row) in rows {
+            graph.append(*time, row);
+        }
+        graph
+    };
+
+    let mut rows = chart_rows();
+    let mut graph = budget_graph(&rows);
+
+    // Edit after a row with more states than the beam search keeps
+    let layer_states = |row_index: usize| {
+        graph
+            .graph
+            .node_weights()
+            .filter(|state| state.row_index == Some(row_index))
+            .count()
+    };
+    let edited = (rows.len() / 2..rows.len())
+        .find(|&i| layer_states(i - 1) > 8)
+        .unwrap();
+    let time = rows[edited].0;
+    graph.replace_row(edited, time, &tap(0));
+    rows[edited] = (time, tap(0));
+
+    // The rebuilt rows only extend the states kept by the beam search, like a fresh build
+    let mut fresh = budget_graph(&rows);
+    assert!(graph.budget_exceeded());
+    assert_eq!(graph.compute_steps(), fresh.compute_steps());
+    assert_eq!(graph.graph.node_count(), fresh.graph.node_count());
+    assert_eq!(graph.graph.edge_count(), fresh.graph.edge_count());
+}
+
+#[test]
+fn hold_edits_match_fresh_build() {
+    let rules = Rules {
+        hold: HoldMode::Sustain,
+        ..Rules::default()
+    };
+    let rules_graph = |rows: &[(f64, Row)]| {
+        let mut graph = StepGraph::new(DanceStage::ddr_solo());
+        graph.set_rules(rules);
+        for (time, row) in rows {
+            graph.append(*time, row);
+        }
+        graph
+    };
+
+    let mut rows = chart_rows();
+    let mut graph = rules_graph(&rows);
+
+    // Turning a hold into a tap frees the foot for the following rows, even if their states match
+    let (edited, column) = rows
+        .iter()
+        .enumerate()
+        .find_map(|(i, (_, row))| {
+            let column = row
+                .columns
+                .iter()
+                .position(|&note| note == NoteKind::HoldHead)?;
+            Some((i, column))
+        })
+        .unwrap();
+    let time = rows[edited].0;
+    let mut row = rows[edited].1.clone();
+    row.columns[column] = NoteKind::Tap;
+    graph.replace_row(edited, time, &row);
+    rows[edited] = (time, row);
+
+    assert_eq!(graph.compute_steps(), rules_graph(&rows).compute_steps());
+}
+
+#[test]
+fn invalid_edits_leave_graph_unchanged() {
+    let rows = chart_rows();
+    let mut graph = fresh_graph(&rows);
+    let wide = Row {
+        columns: vec![NoteKind::Tap; 5],
+    };
+    let error = Err(Error::ColumnCount {
+        expected: 4,
+        actual: 5,
+    });
+
+    let middle = rows.len() / 2;
+    assert_eq!(graph.try_replace_row(middle, rows[middle].0, &wide), error);
+    assert_eq!(graph.try_insert_row(middle, rows[middle].0, &wide), error);
+
+    let mut fresh = fresh_graph(&rows);
+    assert_eq!(graph.compute_steps(), fresh.compute_steps());
+    assert_eq!(graph.graph.node_count(), fresh.graph.node_count());
+    assert_eq!(graph.graph.edge_count(), fresh.graph.edge_count());
+}
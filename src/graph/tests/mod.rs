@@ -3,7 +3,37 @@ use danceparser::{NoteKind, Row};
 
 mod basic;
 mod brackets;
+mod budget;
+mod dot;
+mod errors;
 mod footswitch_jacks;
+mod incremental;
+mod permutations;
+mod probabilities;
+mod profiles;
+mod rules;
+mod tech;
+mod timing;
+
+/// A row of the DDR solo stage with a single tap.
+pub(crate) fn tap(column: usize) -> Row {
+    let mut columns = vec![NoteKind::Empty; 4];
+    columns[column] = NoteKind::Tap;
+    Row { columns }
+}
+
+/// A row written like `1001`, with `1` for taps and anything else for empty columns.
+pub(crate) fn row(columns: &str) -> Row {
+    Row {
+        columns: columns
+            .chars()
+            .map(|c| match c {
+                '1' => NoteKind::Tap,
+                _ => NoteKind::Empty,
+            })
+            .collect(),
+    }
+}
 
 #[test]
 fn walk_into_jump() {
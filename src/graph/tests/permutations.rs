@@ -0,0 +1,34 @@
+use crate::{DanceStage, HasPressRequirement, StepGraph};
+use danceparser::SMChart;
+use danceparser::view::{NoteView, NoteViewer};
+use std::collections::HashSet;
+use std::io::Cursor;
+
+#[test]
+fn cached_permutations_match_computed_ones() {
+    let chart = SMChart::from_sm(Cursor::new(include_str!("../../../Eientewi Set 12A.sm")))
+        .expect("Failed to parse StepMania chart");
+    let rows = NoteViewer::new(&chart, &chart.notes[0]).collect::<Vec<_>>();
+
+    let mut cached = StepGraph::new(DanceStage::ddr_solo());
+    let mut uncached = StepGraph::new(DanceStage::ddr_solo());
+    for &NoteView { time, row } in &rows {
+        cached.append(time, row);
+        uncached.permutation_cache.clear();
+        uncached.append(time, row);
+    }
+    assert_eq!(cached.compute_steps(), uncached.compute_steps());
+
+    // Permutations are computed once per press mask
+    let press_masks = rows
+        .iter()
+        .map(|view| {
+            view.row
+                .columns
+                .iter()
+                .map(|note| note.require_press())
+                .collect::<Vec<_>>()
+        })
+        .collect::<HashSet<_>>();
+    assert_eq!(cached.permutation_cache.len(), press_masks.len());
+}
@@ -0,0 +1,45 @@
+use super::tap;
+use crate::{DanceStage, StepGraph};
+use approx::assert_relative_eq;
+
+fn walk_graph() -> StepGraph {
+    let dance_stage = DanceStage::ddr_solo();
+    let mut graph = StepGraph::new(dance_stage);
+    for (time, column) in [(0.0, 0), (0.5, 1), (1.0, 2), (1.5, 3)] {
+        graph.append(time, &tap(column));
+    }
+    graph
+}
+
+#[test]
+fn test_probabilities_sum_to_one() {
+    let graph = walk_graph();
+    let soft = graph.compute_probabilities(100.0);
+
+    assert_eq!(soft.rows.len(), 4);
+    for row in &soft.rows {
+        let total = row.candidates.iter().map(|c| c.probability).sum::<f64>();
+        assert_relative_eq!(total, 1.0, epsilon = 1e-9);
+    }
+}
+
+#[test]
+fn test_probabilities_match_hard_path() {
+    let mut graph = walk_graph();
+    let soft = graph.compute_probabilities(1.0);
+    let path = graph.compute_path();
+
+    for (row, columns) in soft.rows.iter().zip(path) {
+        assert_eq!(row.best().unwrap().columns, columns);
+    }
+    assert!(soft.confidence > 0.9);
+}
+
+#[test]
+fn test_probabilities_temperature() {
+    let graph = walk_graph();
+    let cold = graph.compute_probabilities(1.0);
+    let hot = graph.compute_probabilities(10000.0);
+
+    assert!(hot.confidence < cold.confidence);
+}
@@ -0,0 +1,68 @@
+use super::row;
+use crate::{DanceStage, HoldMode, Profile, StepEntry, StepGraph, Tech};
+
+fn steps(profile: Profile, rows: &[&str], interval: f64) -> Vec<StepEntry> {
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    graph.set_cost_weights(profile.cost_weights());
+    graph.set_rules(profile.rules());
+    for (i, columns) in rows.iter().enumerate() {
+        graph.append(i as f64 * interval, &row(columns));
+    }
+    graph.compute_steps()
+}
+
+fn count(steps: &[StepEntry], techs: &[Tech]) -> usize {
+    steps
+        .iter()
+        .filter(|step| techs.iter().any(|&tech| step.tech.contains(tech)))
+        .count()
+}
+
+const CROSSOVERS: &[Tech] = &[Tech::FrontCrossover, Tech::BackCrossover];
+
+#[test]
+fn tech_crosses_over_instead_of_footswitching() {
+    let rows = ["1000", "1000", "0100", "0100", "0001", "0001"];
+    let default = steps(Profile::Default, &rows, 0.6);
+    let tech = steps(Profile::Tech, &rows, 0.6);
+
+    assert_eq!(count(&default, &[Tech::Footswitch]), 1);
+    assert_eq!(count(&default, CROSSOVERS), 0);
+    assert_eq!(count(&tech, &[Tech::Footswitch]), 0);
+    assert!(count(&tech, CROSSOVERS) > 0);
+}
+
+#[test]
+fn bar_user_doublesteps_instead_of_crossing_over() {
+    let rows = ["1000", "0001", "0100", "1000", "0010", "0001"];
+    let default = steps(Profile::Default, &rows, 0.15);
+    let bar_user = steps(Profile::BarUser, &rows, 0.15);
+
+    assert!(count(&default, CROSSOVERS) > 0);
+    assert_eq!(count(&default, &[Tech::Doublestep]), 0);
+    assert_eq!(count(&bar_user, CROSSOVERS), 0);
+    assert!(count(&bar_user, &[Tech::Doublestep]) > 0);
+}
+
+#[test]
+fn bracket_profile_brackets_more() {
+    let rows = ["1100", "0011", "1100", "0011", "1010", "0101"];
+    let default = count(&steps(Profile::Default, &rows, 0.3), &[Tech::Bracket]);
+    let bracket = count(&steps(Profile::Bracket, &rows, 0.3), &[Tech::Bracket]);
+    let beginner = count(&steps(Profile::Beginner, &rows, 0.3), &[Tech::Bracket]);
+
+    assert!(bracket > default, "{bracket} <= {default}");
+    assert!(default > 0);
+    assert_eq!(beginner, 0);
+}
+
+#[test]
+fn profile_rules_and_names() {
+    let beginner = Profile::Beginner.rules();
+    assert_eq!(beginner.hold, HoldMode::Sustain);
+    assert!(beginner.forbidden.contains(Tech::Bracket));
+    assert!(beginner.forbidden.contains(Tech::Doublestep));
+    for profile in Profile::ALL {
+        assert_eq!(Profile::parse(profile.name()), Some(profile));
+    }
+}
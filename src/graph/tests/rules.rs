@@ -0,0 +1,171 @@
+use super::tap;
+use crate::{
+    CostWeights, DanceStage, FootPart, HoldMode, MineMode, Rules, StepEntry, StepGraph, Tech,
+};
+use danceparser::view::{NoteView, NoteViewer};
+use danceparser::{NoteKind, Row, SMChart};
+use std::io::Cursor;
+
+fn row(notes: [NoteKind; 4]) -> Row {
+    Row {
+        columns: notes.to_vec(),
+    }
+}
+
+fn hold_chart(rules: Rules) -> StepGraph {
+    use NoteKind::*;
+
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    graph.set_rules(rules);
+    graph.append(0.0, &row([HoldHead, Empty, Empty, Empty]));
+    graph.append(0.25, &tap(3));
+    graph.append(0.5, &tap(1));
+    graph.append(0.75, &tap(3));
+    graph.append(1.0, &tap(2));
+    graph.append(1.25, &row([Tail, Empty, Empty, Empty]));
+    graph
+}
+
+#[test]
+fn sustained_holds() {
+    let free = hold_chart(Rules::default()).compute_path();
+    assert!(
+        free[..5]
+            .iter()
+            .any(|columns| columns.0[0] != FootPart::LeftHeel)
+    );
+
+    let sustained = hold_chart(Rules {
+        hold: HoldMode::Sustain,
+        ..Rules::default()
+    })
+    .compute_path();
+    assert!(
+        sustained[..5]
+            .iter()
+            .all(|columns| columns.0[0] == FootPart::LeftHeel),
+        "{sustained:?}"
+    );
+}
+
+#[test]
+fn changing_rules_rebuilds_graph() {
+    let mut graph = hold_chart(Rules::default());
+    graph.set_rules(Rules {
+        hold: HoldMode::Sustain,
+        ..Rules::default()
+    });
+
+    let rebuilt = graph.compute_path();
+    let sustained = hold_chart(Rules {
+        hold: HoldMode::Sustain,
+        ..Rules::default()
+    })
+    .compute_path();
+    assert_eq!(rebuilt, sustained);
+}
+
+#[test]
+fn forbidden_mines() {
+    use NoteKind::*;
+
+    // The right foot would rather stay on the mine than cross over to the left
+    let mine_chart = |mine: MineMode| {
+        let mut graph = StepGraph::new(DanceStage::ddr_solo());
+        graph.set_rules(Rules {
+            mine,
+            ..Rules::default()
+        });
+        graph.append(0.0, &tap(0));
+        graph.append(0.5, &tap(3));
+        graph.append(0.6, &row([Tap, Empty, Empty, Mine]));
+        graph.compute_path()
+    };
+
+    assert_eq!(mine_chart(MineMode::Ignore)[2].0[3], FootPart::RightHeel);
+    for mine in [MineMode::Avoid, MineMode::Forbid] {
+        assert_eq!(mine_chart(mine)[2].0[3], FootPart::None);
+    }
+}
+
+fn chart_steps(rules: Rules) -> (Vec<StepEntry>, Vec<usize>) {
+    let chart = SMChart::from_sm(Cursor::new(include_str!("../../../Eientewi Set 12A.sm")))
+        .expect("Failed to parse StepMania chart");
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    graph.set_rules(rules);
+    for NoteView { time, row } in NoteViewer::new(&chart, &chart.notes[0]) {
+        graph.append(time, row);
+    }
+    (graph.compute_steps(), graph.rule_violations().to_vec())
+}
+
+#[test]
+fn forbidden_tech() {
+    let (default, violations) = chart_steps(Rules::default());
+    assert!(violations.is_empty());
+
+    for tech in [
+        Tech::Doublestep,
+        Tech::FrontCrossover,
+        Tech::BackCrossover,
+        Tech::Bracket,
+        Tech::Footswitch,
+    ] {
+        let count =
+            |steps: &[StepEntry]| steps.iter().filter(|step| step.tech.contains(tech)).count();
+        assert!(count(&default) > 0, "{tech}");
+
+        // The chart never forces any of them
+        let (steps, violations) = chart_steps(Rules::default().forbid(tech));
+        assert_eq!(steps.len(), default.len());
+        assert_eq!(count(&steps), 0, "{tech}");
+        assert!(violations.is_empty(), "{tech}");
+    }
+}
+
+#[test]
+fn forced_violation() {
+    use NoteKind::*;
+
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    graph.set_rules(Rules::default().forbid(Tech::Bracket));
+    graph.append(0.0, &tap(0));
+    graph.append(0.5, &row([Tap, Tap, Tap, Empty]));
+    graph.append(1.0, &tap(3));
+
+    let steps = graph.compute_steps();
+    assert_eq!(steps.len(), 3);
+    assert!(steps[1].tech.contains(Tech::Bracket));
+    assert_eq!(graph.rule_violations(), [1]);
+
+    // Rebuilding the graph without the rule forgets the violation
+    graph.set_rules(Rules::default());
+    assert!(graph.rule_violations().is_empty());
+}
+
+#[test]
+fn forbidden_doublestep_over_tail() {
+    use NoteKind::*;
+
+    // The tail doesn't step, so the last tap is still compared with the one before the tail
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    graph.set_rules(Rules::default().forbid(Tech::Doublestep));
+    graph.set_cost_weights(CostWeights {
+        doublestep: 0.0,
+        ..CostWeights::default()
+    });
+    graph.append(0.0, &row([HoldHead, Empty, Empty, Empty]));
+    graph.append(0.5, &tap(1));
+    graph.append(1.0, &row([Tail, Empty, Empty, Empty]));
+    graph.append(1.5, &tap(3));
+
+    let steps = graph.compute_steps();
+    assert_eq!(steps.len(), 4);
+    assert!(
+        steps
+            .iter()
+            .all(|step| !step.tech.contains(Tech::Doublestep)),
+        "{steps:?}"
+    );
+    assert!(graph.rule_violations().is_empty());
+}
@@ -0,0 +1,79 @@
+use crate::{DanceStage, StepGraph, Tech, TechTags};
+use danceparser::{NoteKind, Row};
+
+#[test]
+fn test_graph_tech_tags() {
+    let dance_stage = DanceStage::ddr_solo();
+    let mut graph = StepGraph::new(dance_stage);
+    graph.append(
+        0.5,
+        &Row {
+            columns: vec![
+                NoteKind::HoldHead,
+                NoteKind::Empty,
+                NoteKind::Empty,
+                NoteKind::Empty,
+            ],
+        },
+    );
+    graph.append(
+        1.0,
+        &Row {
+            columns: vec![
+                NoteKind::Empty,
+                NoteKind::Empty,
+                NoteKind::Empty,
+                NoteKind::Tap,
+            ],
+        },
+    );
+    graph.append(
+        1.5,
+        &Row {
+            columns: vec![
+                NoteKind::Tail,
+                NoteKind::Empty,
+                NoteKind::Empty,
+                NoteKind::Empty,
+            ],
+        },
+    );
+    graph.append(
+        2.0,
+        &Row {
+            columns: vec![
+                NoteKind::Empty,
+                NoteKind::Empty,
+                NoteKind::Empty,
+                NoteKind::Tap,
+            ],
+        },
+    );
+    graph.append(
+        2.5,
+        &Row {
+            columns: vec![
+                NoteKind::Tap,
+                NoteKind::Empty,
+                NoteKind::Empty,
+                NoteKind::Tap,
+            ],
+        },
+    );
+
+    let tags = graph
+        .compute_steps()
+        .into_iter()
+        .map(|step| step.tech)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tags,
+        vec![
+            TechTags::empty(),
+            TechTags::from_iter([Tech::HoldStep]),
+            TechTags::empty(),
+            TechTags::from_iter([Tech::Jack]),
+            TechTags::from_iter([Tech::Jump, Tech::Jack]),
+        ]
+    );
+}
@@ -0,0 +1,22 @@
+use super::tap;
+use crate::{DanceStage, StepGraph};
+
+#[test]
+fn warped_rows() {
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    // Identical timestamps (warps), then going back in time
+    for (time, column) in [(0.0, 0), (0.0, 3), (1.0, 1), (0.5, 2), (0.5, 2), (2.0, 0)] {
+        graph.append(time, &tap(column));
+    }
+
+    let steps = graph.try_compute_steps().unwrap();
+    assert_eq!(steps.len(), 6);
+    assert!(
+        steps
+            .iter()
+            .all(|step| step.cost.is_finite() && step.cost.0 >= 0.0)
+    );
+    // Simultaneous rows are stepped with different feet, like a jump
+    assert_eq!(steps[0].activated_columns.to_string(), "L---");
+    assert_eq!(steps[1].activated_columns.to_string(), "---R");
+}
@@ -0,0 +1,216 @@
+//! Chart format independent input.
+//!
+//! The predictor only needs the time and the notes of every row, so callers with their own note
+//! model can implement [`InputRow`] instead of constructing an [`danceparser::SMChart`].
+use crate::error::Error;
+use crate::graph::{StepEntry, StepGraph};
+use crate::stage::DanceStage;
+use danceparser::view::NoteView;
+use danceparser::{NoteKind, Row};
+
+/// Kind of note on a single column of a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Note {
+    #[default]
+    Empty,
+    Tap,
+    HoldHead,
+    RollHead,
+    Tail,
+    Mine,
+    Lift,
+    Fake,
+}
+
+impl From<NoteKind> for Note {
+    fn from(kind: NoteKind) -> Self {
+        match kind {
+            NoteKind::Empty | NoteKind::AutoKeysounds => Note::Empty,
+            NoteKind::Tap => Note::Tap,
+            NoteKind::HoldHead => Note::HoldHead,
+            NoteKind::RollHead => Note::RollHead,
+            NoteKind::Tail => Note::Tail,
+            NoteKind::Mine => Note::Mine,
+            NoteKind::Lift => Note::Lift,
+            NoteKind::Fake => Note::Fake,
+        }
+    }
+}
+
+impl From<Note> for NoteKind {
+    fn from(note: Note) -> Self {
+        match note {
+            Note::Empty => NoteKind::Empty,
+            Note::Tap => NoteKind::Tap,
+            Note::HoldHead => NoteKind::HoldHead,
+            Note::RollHead => NoteKind::RollHead,
+            Note::Tail => NoteKind::Tail,
+            Note::Mine => NoteKind::Mine,
+            Note::Lift => NoteKind::Lift,
+            Note::Fake => NoteKind::Fake,
+        }
+    }
+}
+
+/// A row of notes at a point in time, with one note per column of the stage.
+pub trait InputRow {
+    /// Time of the row in seconds.
+    fn time(&self) -> f64;
+
+    /// Note of every column, from left to right.
+    fn notes(&self) -> impl Iterator<Item = Note> + '_;
+
+    fn to_row(&self) -> Row {
+        Row {
+            columns: self.notes().map(NoteKind::from).collect(),
+        }
+    }
+}
+
+impl InputRow for NoteView<'_> {
+    fn time(&self) -> f64 {
+        self.time
+    }
+
+    fn notes(&self) -> impl Iterator<Item = Note> + '_ {
+        self.row.columns.iter().copied().map(Note::from)
+    }
+
+    fn to_row(&self) -> Row {
+        self.row.clone()
+    }
+}
+
+impl InputRow for (f64, &[Note]) {
+    fn time(&self) -> f64 {
+        self.0
+    }
+
+    fn notes(&self) -> impl Iterator<Item = Note> + '_ {
+        self.1.iter().copied()
+    }
+}
+
+impl InputRow for (f64, Vec<Note>) {
+    fn time(&self) -> f64 {
+        self.0
+    }
+
+    fn notes(&self) -> impl Iterator<Item = Note> + '_ {
+        self.1.iter().copied()
+    }
+}
+
+impl<const N: usize> InputRow for (f64, [Note; N]) {
+    fn time(&self) -> f64 {
+        self.0
+    }
+
+    fn notes(&self) -> impl Iterator<Item = Note> + '_ {
+        self.1.iter().copied()
+    }
+}
+
+impl<T: InputRow + ?Sized> InputRow for &T {
+    fn time(&self) -> f64 {
+        (**self).time()
+    }
+
+    fn notes(&self) -> impl Iterator<Item = Note> + '_ {
+        (**self).notes()
+    }
+
+    fn to_row(&self) -> Row {
+        (**self).to_row()
+    }
+}
+
+impl StepGraph {
+    /// Same as [`StepGraph::append`], but accepts any [`InputRow`].
+    pub fn append_input(&mut self, row: impl InputRow) {
+        self.append(row.time(), &row.to_row());
+    }
+
+    /// Same as [`StepGraph::try_append`], but accepts any [`InputRow`].
+    pub fn try_append_input(&mut self, row: impl InputRow) -> Result<(), Error> {
+        self.try_append(row.time(), &row.to_row())
+    }
+}
+
+/// Generate the steps of any sequence of rows, without going through a parsed chart.
+pub fn generate_steps_from<R: InputRow>(
+    dance_stage: DanceStage,
+    rows: impl IntoIterator<Item = R>,
+) -> Vec<StepEntry> {
+    let mut graph = StepGraph::new(dance_stage);
+
+    for row in rows {
+        graph.append_input(row);
+    }
+
+    graph.compute_steps()
+}
+
+/// Same as [`generate_steps_from`], but returns an error instead of panicking on invalid rows.
+pub fn try_generate_steps_from<R: InputRow>(
+    dance_stage: DanceStage,
+    rows: impl IntoIterator<Item = R>,
+) -> Result<Vec<StepEntry>, Error> {
+    let mut graph = StepGraph::new(dance_stage);
+
+    for row in rows {
+        graph.try_append_input(row)?;
+    }
+
+    graph.try_compute_steps()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_steps;
+    use danceparser::SMChart;
+    use danceparser::view::NoteViewer;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_matches_note_viewer() {
+        let chart = SMChart::from_sm(Cursor::new(include_str!("../Eientewi Set 12A.sm")))
+            .expect("Failed to parse StepMania chart");
+
+        for diff in &chart.notes {
+            let viewer = NoteViewer::new(&chart, diff);
+            let rows = viewer
+                .map(|view| (view.time(), view.notes().collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                generate_steps_from(DanceStage::ddr_solo(), &rows),
+                generate_steps(viewer)
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_rows() {
+        let rows = [
+            (0.0, [Note::Tap, Note::Empty, Note::Empty, Note::Empty]),
+            (0.5, [Note::Empty, Note::Empty, Note::Empty, Note::Tap]),
+        ];
+        assert_eq!(
+            try_generate_steps_from(DanceStage::ddr_solo(), rows)
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let short: &[Note] = &[Note::Tap, Note::Empty];
+        assert!(matches!(
+            try_generate_steps_from(DanceStage::ddr_solo(), [(0.0, short)]),
+            Err(Error::ColumnCount {
+                expected: 4,
+                actual: 2
+            })
+        ));
+    }
+}
@@ -1,28 +1,74 @@
 //! Heavily inspired by https://mjvotaw.github.io/posts/step-annotation/step-annotations
+pub(crate) mod annotations;
 pub(crate) mod cost;
+pub(crate) mod difficulty;
+pub(crate) mod error;
+pub(crate) mod eval;
+#[cfg(feature = "serde")]
+pub mod export;
 pub(crate) mod extensions;
 pub(crate) mod feet;
+pub(crate) mod fit;
 pub(crate) mod graph;
+pub(crate) mod input;
+pub(crate) mod parallel;
+pub(crate) mod patterns;
+pub(crate) mod predictor;
+pub(crate) mod profile;
+pub(crate) mod segment;
+pub(crate) mod ssc;
 pub(crate) mod stage;
 pub(crate) mod state;
+pub(crate) mod stats;
+pub(crate) mod tech;
 
-use danceparser::view::{NoteView, NoteViewer};
+use danceparser::view::NoteViewer;
 
+pub use crate::annotations::{FootingError, read_footings, strip_footings, write_footings};
+pub use crate::cost::{CostTerm, CostWeights};
+pub use crate::difficulty::DifficultyEstimate;
+pub use crate::error::Error;
+pub use crate::eval::{Accuracy, Confusion, Evaluation, Section, evaluate};
 pub use crate::extensions::HasPressRequirement;
-pub use crate::feet::{FootPart, FootPlacement};
+pub use crate::feet::{FootPart, FootPlacement, Side};
+pub use crate::fit::{AnnotatedChart, FitOptions, FitReport, WeightChange, fit_weights};
+pub use crate::graph::Progress;
 pub use crate::graph::StepEntry;
 pub use crate::graph::StepGraph;
-pub use crate::stage::DanceStage;
+pub use crate::graph::{HoldMode, MineMode, Rules};
+pub use crate::graph::{PlacementProbability, RowProbabilities, SoftSteps};
+pub use crate::input::{InputRow, Note, generate_steps_from, try_generate_steps_from};
+pub use crate::parallel::{StepJob, generate_steps_parallel, parallel_map};
+pub use crate::patterns::{PatternKind, PatternMatch, count_patterns, find_patterns};
+pub use crate::predictor::{
+    ChartKey, ChartPredictions, Prediction, Predictor, PredictorBuilder, Strategy,
+};
+pub use crate::profile::Profile;
+pub use crate::segment::{find_segments, generate_steps_segmented};
+pub use crate::ssc::ssc_to_sm;
+pub use crate::stage::{DanceStage, StagePosition};
+pub use crate::stats::ChartStats;
+pub use crate::tech::{Tech, TechTags};
 
 pub fn generate_steps(viewer: NoteViewer) -> Vec<StepEntry> {
-    let dance_stage = DanceStage::ddr_solo();
-    let mut graph = StepGraph::new(dance_stage);
+    generate_steps_with_stage(DanceStage::ddr_solo(), viewer)
+}
 
-    for NoteView { time, row, .. } in viewer {
-        graph.append(time, row);
-    }
+pub fn generate_steps_with_stage(dance_stage: DanceStage, viewer: NoteViewer) -> Vec<StepEntry> {
+    generate_steps_from(dance_stage, viewer)
+}
+
+/// Same as [`generate_steps`], but returns an error instead of panicking on invalid charts.
+pub fn try_generate_steps(viewer: NoteViewer) -> Result<Vec<StepEntry>, Error> {
+    try_generate_steps_with_stage(DanceStage::ddr_solo(), viewer)
+}
 
-    graph.compute_steps()
+/// Same as [`generate_steps_with_stage`], but returns an error instead of panicking on invalid charts.
+pub fn try_generate_steps_with_stage(
+    dance_stage: DanceStage,
+    viewer: NoteViewer,
+) -> Result<Vec<StepEntry>, Error> {
+    try_generate_steps_from(dance_stage, viewer)
 }
 
 #[cfg(test)]
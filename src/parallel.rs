@@ -0,0 +1,113 @@
+use crate::graph::StepEntry;
+use crate::stage::DanceStage;
+use danceparser::view::NoteViewer;
+use danceparser::{NotesData, SMChart};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A single difficulty of a chart to generate the steps of.
+#[derive(Debug, Clone)]
+pub struct StepJob<'a> {
+    pub chart: &'a SMChart,
+    pub notes_data: &'a NotesData,
+    pub stage: DanceStage,
+}
+
+impl<'a> StepJob<'a> {
+    pub fn new(chart: &'a SMChart, notes_data: &'a NotesData, stage: DanceStage) -> Self {
+        StepJob {
+            chart,
+            notes_data,
+            stage,
+        }
+    }
+
+    pub fn run(&self) -> Vec<StepEntry> {
+        crate::generate_steps_with_stage(
+            self.stage.clone(),
+            NoteViewer::new(self.chart, self.notes_data),
+        )
+    }
+}
+
+/// Apply `f` to every item using up to `workers` threads.
+///
+/// Results are returned in the same order as the items, regardless of which thread processed them.
+/// A panic in `f` is propagated once every thread has stopped.
+pub fn parallel_map<T: Sync, R: Send>(
+    items: &[T],
+    workers: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let workers = workers.clamp(1, items.len().max(1));
+    if workers == 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next_item = AtomicUsize::new(0);
+    let mut results = items.iter().map(|_| None).collect::<Vec<_>>();
+    std::thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next_item.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(i) else {
+                            break done;
+                        };
+                        done.push((i, f(item)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            let done = handle
+                .join()
+                .unwrap_or_else(|payload| std::panic::resume_unwind(payload));
+            for (i, result) in done {
+                results[i] = Some(result);
+            }
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|result| result.expect("Every item should have been processed"))
+        .collect()
+}
+
+/// Generate the steps of every job using up to `workers` threads.
+///
+/// The result is identical to running [`StepJob::run`] on each job in order.
+pub fn generate_steps_parallel(jobs: &[StepJob], workers: usize) -> Vec<Vec<StepEntry>> {
+    parallel_map(jobs, workers, StepJob::run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_steps;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let chart = SMChart::from_sm(Cursor::new(include_str!("../Eientewi Set 12A.sm")))
+            .expect("Failed to parse StepMania chart");
+
+        let sequential = chart
+            .notes
+            .iter()
+            .map(|diff| generate_steps(NoteViewer::new(&chart, diff)))
+            .collect::<Vec<_>>();
+
+        let jobs = chart
+            .notes
+            .iter()
+            .map(|diff| StepJob::new(&chart, diff, DanceStage::ddr_solo()))
+            .collect::<Vec<_>>();
+        for workers in [1, 3, 16] {
+            assert_eq!(generate_steps_parallel(&jobs, workers), sequential);
+        }
+    }
+}
@@ -0,0 +1,377 @@
+use crate::feet::{FootPart, Side};
+use crate::graph::StepEntry;
+use crate::stage::DanceStage;
+use crate::stats::STREAM_THRESHOLD;
+use crate::tech::Tech;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// Maximum time between two steps for them to be considered part of the same pattern.
+const PATTERN_MAX_GAP: f64 = 1.0;
+/// Minimum amount of steps in a drill.
+const DRILL_MIN_LENGTH: usize = 5;
+/// Minimum amount of steps in a crossover stream.
+const CROSSOVER_STREAM_MIN_LENGTH: usize = 4;
+
+/// Named step pattern.
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum PatternKind {
+    /// A foot moves straight across the center between two of its steps (e.g `DLU`).
+    Candle,
+    /// Four alternating steps on four different columns going across the stage (e.g `LDUR`).
+    Staircase,
+    /// Alternating steps going back and forth between two columns, at least five steps long.
+    Drill,
+    /// Two quick steps surrounded by longer gaps.
+    Gallop,
+    /// A staircase going back on itself (e.g `LDURUD`).
+    Sweep,
+    /// Four alternating steps between two horizontally aligned columns (e.g `LRLR`).
+    Box,
+    /// Four alternating steps between two vertically aligned columns (e.g `UDUD`).
+    Tower,
+    /// A stream of alternating steps containing crossovers.
+    CrossoverStream,
+}
+
+/// Pattern found in a chart.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatternMatch {
+    pub kind: PatternKind,
+    /// Range of [`StepEntry::row_index`] covered by the pattern.
+    pub rows: Range<usize>,
+    /// Foot used by each step of the pattern.
+    pub feet: Vec<Side>,
+}
+
+/// A step hit by a single foot.
+#[derive(Debug, Clone, Copy)]
+struct SingleStep<'a> {
+    entry: &'a StepEntry,
+    side: Side,
+    column: usize,
+}
+
+impl<'a> SingleStep<'a> {
+    fn new(entry: &'a StepEntry) -> Option<Self> {
+        let [left, right] = [Side::Left, Side::Right].map(|side| {
+            entry
+                .activated_columns
+                .get_foot_part_index(FootPart::heel(side))
+        });
+        let (side, column) = match (left, right) {
+            (Some(column), None) => (Side::Left, column),
+            (None, Some(column)) => (Side::Right, column),
+            _ => return None,
+        };
+
+        Some(SingleStep {
+            entry,
+            side,
+            column,
+        })
+    }
+}
+
+fn pattern_match(kind: PatternKind, steps: &[SingleStep]) -> PatternMatch {
+    PatternMatch {
+        kind,
+        rows: steps[0].entry.row_index..steps[steps.len() - 1].entry.row_index + 1,
+        feet: steps.iter().map(|step| step.side).collect(),
+    }
+}
+
+fn is_staircase(stage: &DanceStage, steps: &[SingleStep]) -> bool {
+    let columns = steps.iter().map(|step| step.column).collect::<Vec<_>>();
+    let distinct = columns
+        .iter()
+        .enumerate()
+        .all(|(i, column)| !columns[..i].contains(column));
+    if !distinct {
+        return false;
+    }
+
+    let x = columns.iter().map(|&c| stage.x(c)).collect::<Vec<_>>();
+    let increasing = x.windows(2).all(|w| w[0] <= w[1]);
+    let decreasing = x.windows(2).all(|w| w[0] >= w[1]);
+    (increasing || decreasing) && x[0] != x[x.len() - 1]
+}
+
+/// Split the steps into runs of alternating single foot steps.
+fn alternating_runs(steps: &[StepEntry]) -> Vec<Vec<SingleStep<'_>>> {
+    let mut runs = Vec::new();
+    let mut run: Vec<SingleStep> = Vec::new();
+    for entry in steps {
+        if entry.activated_columns.part_count() == 0 {
+            continue;
+        }
+
+        let step = SingleStep::new(entry);
+        let continues = match (step, run.last()) {
+            (Some(step), Some(prev)) => {
+                step.side != prev.side && step.entry.time.0 - prev.entry.time.0 <= PATTERN_MAX_GAP
+            }
+            _ => false,
+        };
+
+        if !continues && !run.is_empty() {
+            runs.push(std::mem::take(&mut run));
+        }
+        if let Some(step) = step {
+            run.push(step);
+        }
+    }
+    if !run.is_empty() {
+        runs.push(run);
+    }
+
+    runs
+}
+
+fn find_run_patterns(stage: &DanceStage, run: &[SingleStep], matches: &mut Vec<PatternMatch>) {
+    for (i, step) in run.iter().enumerate().skip(2) {
+        if step.entry.tech.contains(Tech::Candle) {
+            matches.push(pattern_match(PatternKind::Candle, &run[i - 2..=i]));
+        }
+    }
+
+    let mut i = 0;
+    while i + 4 <= run.len() {
+        if !is_staircase(stage, &run[i..i + 4]) {
+            i += 1;
+            continue;
+        }
+
+        let sweep = i + 6 <= run.len()
+            && run[i + 4].column == run[i + 2].column
+            && run[i + 5].column == run[i + 1].column;
+        if sweep {
+            matches.push(pattern_match(PatternKind::Sweep, &run[i..i + 6]));
+            i += 6;
+        } else {
+            matches.push(pattern_match(PatternKind::Staircase, &run[i..i + 4]));
+            i += 4;
+        }
+    }
+
+    // Alternating between two columns, with each foot staying on its own column
+    let mut start = 0;
+    while start + 1 < run.len() {
+        let mut end = start + 2;
+        while end < run.len() && run[end].column == run[end - 2].column {
+            end += 1;
+        }
+
+        let length = end - start;
+        let (a, b) = (run[start].column, run[start + 1].column);
+        if a != b && length >= DRILL_MIN_LENGTH {
+            matches.push(pattern_match(PatternKind::Drill, &run[start..end]));
+        } else if a != b && length == 4 {
+            if stage.y(a) == stage.y(b) {
+                matches.push(pattern_match(PatternKind::Box, &run[start..end]));
+            } else if stage.x(a) == stage.x(b) {
+                matches.push(pattern_match(PatternKind::Tower, &run[start..end]));
+            }
+        }
+
+        start = end - 1;
+    }
+
+    let mut start = 0;
+    while start < run.len() {
+        let mut end = start + 1;
+        while end < run.len()
+            && run[end].entry.time.0 - run[end - 1].entry.time.0 <= STREAM_THRESHOLD
+        {
+            end += 1;
+        }
+
+        let stream = &run[start..end];
+        let has_crossover = stream.iter().any(|step| {
+            step.entry.tech.contains(Tech::FrontCrossover)
+                || step.entry.tech.contains(Tech::BackCrossover)
+        });
+        if stream.len() >= CROSSOVER_STREAM_MIN_LENGTH && has_crossover {
+            matches.push(pattern_match(PatternKind::CrossoverStream, stream));
+        }
+
+        start = end;
+    }
+}
+
+fn find_gallops(steps: &[StepEntry], matches: &mut Vec<PatternMatch>) {
+    let stepping = steps
+        .iter()
+        .filter(|entry| entry.activated_columns.part_count() != 0)
+        .collect::<Vec<_>>();
+
+    let gap = |a: usize, b: usize| stepping[b].time.0 - stepping[a].time.0;
+    for i in 0..stepping.len().saturating_sub(1) {
+        let (Some(first), Some(second)) = (
+            SingleStep::new(stepping[i]),
+            SingleStep::new(stepping[i + 1]),
+        ) else {
+            continue;
+        };
+        if first.side == second.side {
+            continue;
+        }
+
+        let short = gap(i, i + 1);
+        let gap_before = i.checked_sub(1).map_or(f64::INFINITY, |p| gap(p, i));
+        let gap_after = stepping
+            .get(i + 2)
+            .map_or(f64::INFINITY, |_| gap(i + 1, i + 2));
+        if short > 0.0 && gap_before >= 2.0 * short && gap_after >= 2.0 * short {
+            matches.push(pattern_match(PatternKind::Gallop, &[first, second]));
+        }
+    }
+}
+
+/// Find named patterns in the predicted steps of a chart, ordered by their first row.
+pub fn find_patterns(stage: &DanceStage, steps: &[StepEntry]) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+    for run in alternating_runs(steps) {
+        find_run_patterns(stage, &run, &mut matches);
+    }
+    find_gallops(steps, &mut matches);
+
+    matches.sort_by_key(|m| (m.rows.start, m.kind));
+    matches
+}
+
+/// Count how many times each pattern kind appears.
+pub fn count_patterns(matches: &[PatternMatch]) -> BTreeMap<PatternKind, usize> {
+    let mut counts = BTreeMap::new();
+    for m in matches {
+        *counts.entry(m.kind).or_default() += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feet::CompactPlacement;
+    use crate::state::State;
+    use crate::tech::classify;
+    use ordered_float::OrderedFloat;
+
+    fn steps(stage: &DanceStage, placements: &[(f64, &str)]) -> Vec<StepEntry> {
+        let mut state = State::new(stage.column_count());
+        placements
+            .iter()
+            .enumerate()
+            .map(|(row_index, &(time, placement))| {
+                let next = state.append(&CompactPlacement::parse(placement));
+                let tech = classify(stage, &state, &next, false);
+                state = next;
+                StepEntry {
+                    time: OrderedFloat(time),
+                    row_index,
+                    activated_columns: state.activated_columns.into(),
+                    columns: state.final_columns.into(),
+                    tech,
+                    cost: OrderedFloat(0.0),
+                }
+            })
+            .collect()
+    }
+
+    fn kinds(stage: &DanceStage, placements: &[(f64, &str)]) -> Vec<(PatternKind, Range<usize>)> {
+        find_patterns(stage, &steps(stage, placements))
+            .into_iter()
+            .map(|m| (m.kind, m.rows))
+            .collect()
+    }
+
+    #[test]
+    fn test_sweep_and_staircase() {
+        let stage = DanceStage::ddr_solo();
+        assert_eq!(
+            kinds(
+                &stage,
+                &[
+                    (0.0, "L---"),
+                    (0.25, "-R--"),
+                    (0.5, "--L-"),
+                    (0.75, "---R"),
+                    (1.0, "--L-"),
+                    (1.25, "-R--"),
+                    (5.0, "L---"),
+                    (5.25, "-R--"),
+                    (5.5, "--L-"),
+                    (5.75, "---R"),
+                ]
+            ),
+            vec![(PatternKind::Sweep, 0..6), (PatternKind::Staircase, 6..10)]
+        );
+    }
+
+    #[test]
+    fn test_drill_tower_candle() {
+        let stage = DanceStage::ddr_solo();
+        assert_eq!(
+            kinds(
+                &stage,
+                &[
+                    (0.0, "L---"),
+                    (0.25, "---R"),
+                    (0.5, "L---"),
+                    (0.75, "---R"),
+                    (1.0, "L---"),
+                    (5.0, "-L--"),
+                    (5.25, "--R-"),
+                    (5.5, "-L--"),
+                    (5.75, "--R-"),
+                    (10.0, "-R--"),
+                    (10.25, "L---"),
+                    (10.5, "--R-"),
+                ]
+            ),
+            vec![
+                (PatternKind::Drill, 0..5),
+                (PatternKind::Tower, 5..9),
+                (PatternKind::Candle, 9..12),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gallop_and_crossover_stream() {
+        let stage = DanceStage::ddr_solo();
+        let matches = find_patterns(
+            &stage,
+            &steps(
+                &stage,
+                &[
+                    (0.0, "L---"),
+                    (0.1, "---R"),
+                    (5.0, "L---"),
+                    (5.125, "-R--"),
+                    (5.25, "---L"),
+                    (5.375, "-R--"),
+                    (5.5, "L---"),
+                ],
+            ),
+        );
+        assert_eq!(
+            matches
+                .iter()
+                .map(|m| (m.kind, m.rows.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (PatternKind::Gallop, 0..2),
+                (PatternKind::CrossoverStream, 2..7),
+            ]
+        );
+        assert_eq!(matches[0].feet, vec![Side::Left, Side::Right]);
+        assert_eq!(
+            count_patterns(&matches),
+            BTreeMap::from([(PatternKind::Gallop, 1), (PatternKind::CrossoverStream, 1)])
+        );
+    }
+}
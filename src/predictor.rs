@@ -0,0 +1,383 @@
+//! Configurable predictor, running many charts with the same settings.
+use crate::cost::{CostTerm, CostWeights};
+use crate::difficulty::DifficultyEstimate;
+use crate::error::Error;
+use crate::graph::{HoldMode, MineMode, Rules, StepEntry, StepGraph};
+use crate::input::InputRow;
+use crate::profile::Profile;
+use crate::segment::{find_segments, try_solve_segments};
+use crate::stage::DanceStage;
+use crate::stats::ChartStats;
+use crate::tech::Tech;
+use danceparser::view::NoteViewer;
+use danceparser::{NotesData, Row, SMChart};
+use std::time::Duration;
+
+/// How the cheapest footing is searched for.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Strategy {
+    /// Search the whole chart at once.
+    #[default]
+    Exact,
+    /// Search the whole chart at once, switching to a beam search once the budget is exceeded,
+    /// see [`StepGraph::set_time_budget`](crate::StepGraph::set_time_budget).
+    TimeBudget(Duration),
+    /// Solve every segment between breaks longer than `break_threshold` seconds independently,
+    /// using up to `workers` threads.
+    Segmented {
+        break_threshold: f64,
+        workers: usize,
+    },
+}
+
+/// Footing predicted for a chart.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Prediction {
+    pub steps: Vec<StepEntry>,
+    pub stats: ChartStats,
+    pub estimate: DifficultyEstimate,
+    /// Whether the time budget was exceeded, in which case the footing may not be the cheapest.
+    pub budget_exceeded: bool,
+    /// Rows where the rules had to be broken, as the chart left no other choice.
+    pub rule_violations: Vec<usize>,
+}
+
+/// Identifies a difficulty within a chart.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChartKey {
+    pub style: String,
+    pub difficulty: String,
+    pub description: String,
+}
+
+impl ChartKey {
+    pub fn new(notes_data: &NotesData) -> Self {
+        ChartKey {
+            style: notes_data.style.clone(),
+            difficulty: notes_data.difficulty.clone(),
+            description: notes_data.description.clone(),
+        }
+    }
+}
+
+/// Predictions of every difficulty of a chart, see [`Predictor::predict_all`].
+///
+/// Every list is in the same order as the difficulties of the chart.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ChartPredictions {
+    pub predictions: Vec<(ChartKey, Prediction)>,
+    /// Difficulties whose style has no known stage.
+    pub unsupported: Vec<ChartKey>,
+    /// Difficulties of a supported style which couldn't be predicted.
+    pub failures: Vec<(ChartKey, Error)>,
+}
+
+impl ChartPredictions {
+    /// Prediction of the first difficulty matching `key`.
+    pub fn get(&self, key: &ChartKey) -> Option<&Prediction> {
+        self.predictions
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, prediction)| prediction)
+    }
+}
+
+/// Predictor of chart footings, see [`Predictor::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct Predictor {
+    stage: Option<DanceStage>,
+    weights: CostWeights,
+    rules: Rules,
+    strategy: Strategy,
+}
+
+impl Predictor {
+    pub fn builder() -> PredictorBuilder {
+        PredictorBuilder::default()
+    }
+
+    /// Builder starting from the settings of this predictor.
+    pub fn to_builder(&self) -> PredictorBuilder {
+        PredictorBuilder {
+            predictor: self.clone(),
+        }
+    }
+
+    /// Stage used for charts of the given style.
+    pub fn stage_for(&self, style: &str) -> Result<DanceStage, Error> {
+        match &self.stage {
+            Some(stage) => Ok(stage.clone()),
+            None => DanceStage::for_style(style)
+                .ok_or_else(|| Error::UnsupportedStyle(style.to_string())),
+        }
+    }
+
+    pub fn cost_weights(&self) -> &CostWeights {
+        &self.weights
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    /// Predict the footing of a single difficulty of a chart.
+    pub fn predict(&self, chart: &SMChart, notes_data: &NotesData) -> Result<Prediction, Error> {
+        let stage = self.stage_for(&notes_data.style)?;
+        self.predict_with_stage(stage, NoteViewer::new(chart, notes_data))
+    }
+
+    /// Predict the footing of every difficulty of a chart.
+    ///
+    /// Difficulties are never skipped silently, the ones that can't be predicted are reported
+    /// in [`ChartPredictions::unsupported`] or [`ChartPredictions::failures`].
+    pub fn predict_all(&self, chart: &SMChart) -> ChartPredictions {
+        let mut result = ChartPredictions::default();
+        for notes_data in &chart.notes {
+            let key = ChartKey::new(notes_data);
+            match self.predict(chart, notes_data) {
+                Ok(prediction) => result.predictions.push((key, prediction)),
+                Err(Error::UnsupportedStyle(_)) => result.unsupported.push(key),
+                Err(e) => result.failures.push((key, e)),
+            }
+        }
+        result
+    }
+
+    /// Predict the footing of any sequence of rows.
+    ///
+    /// Uses the configured stage, or [`DanceStage::ddr_solo`] if there is none.
+    pub fn predict_rows<R: InputRow>(
+        &self,
+        rows: impl IntoIterator<Item = R>,
+    ) -> Result<Prediction, Error> {
+        let stage = self.stage.clone().unwrap_or_else(DanceStage::ddr_solo);
+        self.predict_with_stage(stage, rows)
+    }
+
+    /// Create an empty graph using the predictor's settings.
+    pub fn new_graph(&self, stage: DanceStage) -> StepGraph {
+        let mut graph = StepGraph::new(stage);
+        graph.set_cost_weights(self.weights);
+        graph.set_rules(self.rules);
+        graph
+    }
+
+    fn predict_with_stage<R: InputRow>(
+        &self,
+        stage: DanceStage,
+        rows: impl IntoIterator<Item = R>,
+    ) -> Result<Prediction, Error> {
+        let (steps, rule_violations, budget_exceeded) = match self.strategy {
+            Strategy::Exact | Strategy::TimeBudget(_) => {
+                let mut graph = self.new_graph(stage.clone());
+                if let Strategy::TimeBudget(budget) = self.strategy {
+                    graph.set_time_budget(budget);
+                }
+                for row in rows {
+                    graph.try_append_input(row)?;
+                }
+                let steps = graph.try_compute_steps()?;
+                (
+                    steps,
+                    graph.rule_violations().to_vec(),
+                    graph.budget_exceeded(),
+                )
+            }
+            Strategy::Segmented {
+                break_threshold,
+                workers,
+            } => {
+                let rows = rows
+                    .into_iter()
+                    .map(|row| (row.time(), row.to_row()))
+                    .collect::<Vec<(f64, Row)>>();
+                let rows = rows
+                    .iter()
+                    .map(|(time, row)| (*time, row))
+                    .collect::<Vec<_>>();
+                let segments = find_segments(&rows, break_threshold);
+                let (steps, rule_violations) =
+                    try_solve_segments(&rows, &segments, workers, || {
+                        self.new_graph(stage.clone())
+                    })?;
+                (steps, rule_violations, false)
+            }
+        };
+
+        let stats = ChartStats::new(&stage, &steps);
+        let estimate = DifficultyEstimate::new(&stats, &steps);
+        Ok(Prediction {
+            steps,
+            stats,
+            estimate,
+            budget_exceeded,
+            rule_violations,
+        })
+    }
+}
+
+/// Builder of a [`Predictor`], every setting defaults to the behaviour of [`crate::generate_steps`].
+#[derive(Debug, Clone, Default)]
+pub struct PredictorBuilder {
+    predictor: Predictor,
+}
+
+impl PredictorBuilder {
+    /// Use `stage` for every chart, instead of choosing it from the chart style.
+    pub fn stage(mut self, stage: DanceStage) -> Self {
+        self.predictor.stage = Some(stage);
+        self
+    }
+
+    pub fn cost_weights(mut self, weights: CostWeights) -> Self {
+        self.predictor.weights = weights;
+        self
+    }
+
+    pub fn weight(mut self, term: CostTerm, weight: f64) -> Self {
+        *self.predictor.weights.get_mut(term) = weight;
+        self
+    }
+
+    pub fn disable(self, term: CostTerm) -> Self {
+        self.weight(term, 0.0)
+    }
+
+    /// Use the cost weights and rules of `profile`.
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.predictor.weights = profile.cost_weights();
+        self.predictor.rules = profile.rules();
+        self
+    }
+
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.predictor.rules = rules;
+        self
+    }
+
+    pub fn hold_mode(mut self, mode: HoldMode) -> Self {
+        self.predictor.rules.hold = mode;
+        self
+    }
+
+    pub fn mine_mode(mut self, mode: MineMode) -> Self {
+        self.predictor.rules.mine = mode;
+        self
+    }
+
+    /// Never perform `tech`, unless the chart leaves no other choice.
+    pub fn forbid(mut self, tech: Tech) -> Self {
+        self.predictor.rules.forbidden.insert(tech);
+        self
+    }
+
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.predictor.strategy = strategy;
+        self
+    }
+
+    pub fn build(self) -> Predictor {
+        self.predictor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_steps;
+    use std::io::Cursor;
+
+    fn chart() -> SMChart {
+        SMChart::from_sm(Cursor::new(include_str!("../Eientewi Set 12A.sm")))
+            .expect("Failed to parse StepMania chart")
+    }
+
+    #[test]
+    fn test_default_matches_generate_steps() {
+        let chart = chart();
+        let predictors = [
+            Predictor::default(),
+            Predictor::builder()
+                .strategy(Strategy::Segmented {
+                    break_threshold: f64::INFINITY,
+                    workers: 2,
+                })
+                .build(),
+        ];
+
+        for diff in &chart.notes {
+            let expected = generate_steps(NoteViewer::new(&chart, diff));
+            for predictor in &predictors {
+                let prediction = predictor.predict(&chart, diff).unwrap();
+                assert_eq!(prediction.steps, expected);
+                assert!(!prediction.budget_exceeded);
+                assert!(prediction.rule_violations.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_weights_change_footing() {
+        let chart = chart();
+        let diff = &chart.notes[0];
+        let default = Predictor::default().predict(&chart, diff).unwrap();
+        let without_facing = Predictor::builder()
+            .disable(CostTerm::Facing)
+            .disable(CostTerm::TwistedFoot)
+            .build()
+            .predict(&chart, diff)
+            .unwrap();
+
+        assert_eq!(default.steps.len(), without_facing.steps.len());
+        assert_ne!(default.steps, without_facing.steps);
+    }
+
+    #[test]
+    fn test_predict_all() {
+        let mut chart = chart();
+        chart.notes[1].style = "kb7-single".to_string();
+
+        let predictions = Predictor::default().predict_all(&chart);
+        assert_eq!(predictions.predictions.len(), chart.notes.len() - 1);
+        assert_eq!(
+            predictions.unsupported,
+            vec![ChartKey::new(&chart.notes[1])]
+        );
+        assert!(predictions.failures.is_empty());
+
+        for diff in chart
+            .notes
+            .iter()
+            .filter(|diff| diff.style == "dance-single")
+        {
+            assert_eq!(
+                predictions.get(&ChartKey::new(diff)).unwrap().steps,
+                generate_steps(NoteViewer::new(&chart, diff))
+            );
+        }
+    }
+
+    #[test]
+    fn test_unsupported_style() {
+        let mut chart = chart();
+        chart.notes[0].style = "kb7-single".to_string();
+
+        assert_eq!(
+            Predictor::default().predict(&chart, &chart.notes[0]),
+            Err(Error::UnsupportedStyle("kb7-single".to_string()))
+        );
+        assert!(
+            Predictor::builder()
+                .stage(DanceStage::ddr_solo())
+                .build()
+                .predict(&chart, &chart.notes[0])
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Named player styles, bundling cost weights and rules.
+use crate::cost::CostWeights;
+use crate::graph::{HoldMode, MineMode, Rules};
+use crate::tech::Tech;
+use std::fmt::{Display, Formatter};
+
+/// Way of playing, different players foot the same chart differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Profile {
+    /// Same as [`CostWeights::default`] and [`Rules::default`].
+    #[default]
+    Default,
+    /// Alternates feet without brackets, keeps feet on holds and never steps on mines,
+    /// unless the chart leaves no other choice.
+    Beginner,
+    /// Crosses over and turns rather than doublestepping or footswitching.
+    Tech,
+    /// Brackets whatever can be bracketed.
+    Bracket,
+    /// Holds the bar behind the stage, so never faces away from the screen
+    /// and doublesteps rather than crossing over.
+    BarUser,
+}
+
+impl Profile {
+    pub const ALL: [Profile; 5] = [
+        Profile::Default,
+        Profile::Beginner,
+        Profile::Tech,
+        Profile::Bracket,
+        Profile::BarUser,
+    ];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Profile::Default => "default",
+            Profile::Beginner => "beginner",
+            Profile::Tech => "tech",
+            Profile::Bracket => "bracket",
+            Profile::BarUser => "bar-user",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|profile| profile.name() == s)
+    }
+
+    pub fn cost_weights(&self) -> CostWeights {
+        let default = CostWeights::default();
+        match self {
+            Profile::Default => default,
+            Profile::Beginner => CostWeights {
+                doublestep: default.doublestep * 10.0,
+                slow_bracket: default.slow_bracket * 10.0,
+                jack: default.jack / 4.0,
+                ..default
+            },
+            Profile::Tech => CostWeights {
+                facing: default.facing / 4.0,
+                twisted_foot: default.twisted_foot / 4.0,
+                doublestep: default.doublestep * 2.0,
+                slow_footswitch: default.slow_footswitch * 2.0,
+                ..default
+            },
+            Profile::Bracket => CostWeights {
+                movement: default.movement * 2.0,
+                slow_bracket: default.slow_bracket / 10.0,
+                ..default
+            },
+            Profile::BarUser => CostWeights {
+                facing: default.facing * 10.0,
+                twisted_foot: default.twisted_foot * 10.0,
+                doublestep: default.doublestep / 4.0,
+                ..default
+            },
+        }
+    }
+
+    pub fn rules(&self) -> Rules {
+        match self {
+            Profile::Beginner => Rules {
+                hold: HoldMode::Sustain,
+                mine: MineMode::Forbid,
+                ..Rules::default()
+                    .forbid(Tech::Bracket)
+                    .forbid(Tech::Doublestep)
+            },
+            Profile::Default | Profile::Tech | Profile::Bracket | Profile::BarUser => {
+                Rules::default()
+            }
+        }
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.name())
+    }
+}
@@ -0,0 +1,203 @@
+//! Splitting of charts at long breaks.
+//!
+//! After a long enough break the player is free to reposition, so the footing before the break
+//! doesn't affect the footing after it. Solving each segment on its own keeps the graphs small,
+//! and lets the segments be solved in parallel.
+use crate::error::Error;
+use crate::graph::{StepEntry, StepGraph};
+use crate::parallel::parallel_map;
+use crate::stage::DanceStage;
+use danceparser::view::{NoteView, NoteViewer};
+use danceparser::{NoteKind, Row};
+use std::ops::Range;
+
+/// Find the ranges of rows separated by breaks longer than `break_threshold` seconds.
+///
+/// A chart is never split while a hold is active, even if the next row is far away.
+pub fn find_segments(rows: &[(f64, &Row)], break_threshold: f64) -> Vec<Range<usize>> {
+    let mut segments = Vec::new();
+    let mut held = Vec::new();
+    let mut start = 0;
+    for (i, (time, row)) in rows.iter().enumerate() {
+        if let Some((prev_time, _)) = i.checked_sub(1).map(|i| rows[i])
+            && time - prev_time > break_threshold
+            && !held.iter().any(|&held| held)
+        {
+            segments.push(start..i);
+            start = i;
+        }
+
+        held.resize(row.columns.len(), false);
+        for (column, note) in row.columns.iter().enumerate() {
+            match note {
+                NoteKind::HoldHead | NoteKind::RollHead => held[column] = true,
+                NoteKind::Tail => held[column] = false,
+                _ => {}
+            }
+        }
+    }
+
+    if start < rows.len() {
+        segments.push(start..rows.len());
+    }
+    segments
+}
+
+/// Generate the steps of a chart, solving every segment between breaks longer than
+/// `break_threshold` seconds independently, using up to `workers` threads.
+///
+/// Row indices are relative to the whole chart, so the result is identical to
+/// [`crate::generate_steps_with_stage`] when the chart has no such break.
+pub fn generate_steps_segmented(
+    dance_stage: DanceStage,
+    viewer: NoteViewer,
+    break_threshold: f64,
+    workers: usize,
+) -> Vec<StepEntry> {
+    let rows = viewer
+        .map(|NoteView { time, row, .. }| (time, row))
+        .collect::<Vec<_>>();
+    let segments = find_segments(&rows, break_threshold);
+    try_solve_segments(&rows, &segments, workers, || {
+        StepGraph::new(dance_stage.clone())
+    })
+    .map(|(steps, _)| steps)
+    .unwrap_or_else(|e| match e {
+        // Same as `StepGraph::compute_steps`
+        Error::NoPath => Vec::new(),
+        e => panic!("{e}"),
+    })
+}
+
+/// Solve every segment with a graph created by `new_graph`.
+///
+/// Returns the steps along with the rows where the rules had to be broken.
+pub(crate) fn try_solve_segments(
+    rows: &[(f64, &Row)],
+    segments: &[Range<usize>],
+    workers: usize,
+    new_graph: impl Fn() -> StepGraph + Sync,
+) -> Result<(Vec<StepEntry>, Vec<usize>), Error> {
+    let segment_steps = parallel_map(segments, workers, |segment| {
+        let mut graph = new_graph();
+        for &(time, row) in &rows[segment.clone()] {
+            graph.try_append(time, row)?;
+        }
+
+        let mut steps = graph.try_compute_steps()?;
+        for step in &mut steps {
+            step.row_index += segment.start;
+        }
+        let violations = graph
+            .rule_violations()
+            .iter()
+            .map(|row_index| row_index + segment.start)
+            .collect::<Vec<_>>();
+        Ok((steps, violations))
+    });
+
+    let mut steps = Vec::with_capacity(rows.len());
+    let mut violations = Vec::new();
+    for segment in segment_steps {
+        let (segment_steps, segment_violations) = segment?;
+        steps.extend(segment_steps);
+        violations.extend(segment_violations);
+    }
+    Ok((steps, violations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_steps;
+    use crate::graph::tests::tap;
+    use danceparser::SMChart;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_find_segments() {
+        let hold = Row {
+            columns: vec![
+                NoteKind::HoldHead,
+                NoteKind::Empty,
+                NoteKind::Empty,
+                NoteKind::Empty,
+            ],
+        };
+        let tail = Row {
+            columns: vec![
+                NoteKind::Tail,
+                NoteKind::Empty,
+                NoteKind::Empty,
+                NoteKind::Empty,
+            ],
+        };
+        let (a, b) = (tap(1), tap(2));
+        let rows = [
+            (0.0, &a),
+            (0.5, &b),
+            (10.0, &hold),
+            (20.0, &tail),
+            (20.5, &a),
+            (25.0, &b),
+        ];
+
+        assert_eq!(find_segments(&rows, 3.0), vec![0..2, 2..5, 5..6]);
+        assert_eq!(find_segments(&rows, 100.0), vec![0..6]);
+        assert_eq!(find_segments(&[], 3.0), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn test_segmented_matches_independent_solves() {
+        let rows = [0, 1, 2, 3, 0, 3].map(tap);
+        let solve = |offset: f64| {
+            let mut graph = StepGraph::new(DanceStage::ddr_solo());
+            for (i, row) in rows.iter().enumerate() {
+                graph.append(offset + i as f64 * 0.25, row);
+            }
+            graph.compute_steps()
+        };
+
+        let chart_rows = rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| (i as f64 * 0.25, row))
+            .chain(
+                rows.iter()
+                    .enumerate()
+                    .map(|(i, row)| (10.0 + i as f64 * 0.25, row)),
+            )
+            .collect::<Vec<_>>();
+        let segments = find_segments(&chart_rows, 5.0);
+        assert_eq!(segments, vec![0..6, 6..12]);
+
+        let mut expected = solve(0.0);
+        expected.extend(solve(10.0).into_iter().map(|mut step| {
+            step.row_index += rows.len();
+            step
+        }));
+
+        for workers in [1, 2] {
+            assert_eq!(
+                try_solve_segments(&chart_rows, &segments, workers, || {
+                    StepGraph::new(DanceStage::ddr_solo())
+                }),
+                Ok((expected.clone(), Vec::new()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_breaks_matches_monolithic() {
+        let chart = SMChart::from_sm(Cursor::new(include_str!("../Eientewi Set 12A.sm")))
+            .expect("Failed to parse StepMania chart");
+
+        for diff in &chart.notes {
+            let viewer = NoteViewer::new(&chart, diff);
+            assert_eq!(
+                generate_steps_segmented(DanceStage::ddr_solo(), viewer, f64::INFINITY, 4),
+                generate_steps(viewer)
+            );
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Loading of `.ssc` simfiles.
+//!
+//! `danceparser` only understands `.sm` files, so `.ssc` files are converted to the `.sm` format
+//! before being parsed. Every `#NOTEDATA` section is turned into a `#NOTES` tag,
+//! and per-chart timing tags are dropped in favour of the song timing.
+use crate::annotations::tags;
+
+#[derive(Default)]
+struct NoteData<'a> {
+    style: &'a str,
+    description: &'a str,
+    chart_name: &'a str,
+    difficulty: &'a str,
+    meter: &'a str,
+}
+
+/// Convert the content of an `.ssc` file to the `.sm` format.
+///
+/// The result can be parsed with [`danceparser::SMChart::from_sm`].
+pub fn ssc_to_sm(simfile: &str) -> String {
+    let mut result = String::with_capacity(simfile.len());
+    let mut note_data = None;
+    for tag in tags(simfile) {
+        if tag.is("NOTEDATA") {
+            note_data = Some(NoteData::default());
+            continue;
+        }
+
+        let Some(note_data) = &mut note_data else {
+            result.push_str(&simfile[tag.range]);
+            result.push('\n');
+            continue;
+        };
+
+        let value = tag.value.trim();
+        match tag.name.to_ascii_uppercase().as_str() {
+            "STEPSTYPE" => note_data.style = value,
+            "DESCRIPTION" => note_data.description = value,
+            "CHARTNAME" => note_data.chart_name = value,
+            "DIFFICULTY" => note_data.difficulty = value,
+            "METER" => note_data.meter = value,
+            "NOTES" | "NOTES2" => {
+                let description = if note_data.description.is_empty() {
+                    note_data.chart_name
+                } else {
+                    note_data.description
+                };
+                let meter = if note_data.meter.is_empty() {
+                    "1"
+                } else {
+                    note_data.meter
+                };
+
+                result.push_str(&format!(
+                    "#NOTES:\n     {}:\n     {description}:\n     {}:\n     {meter}:\n     :\n{value}\n;\n",
+                    note_data.style, note_data.difficulty,
+                ));
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use danceparser::SMChart;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_ssc_to_sm() {
+        let simfile = "#VERSION:0.83;\n\
+            #TITLE:Test;\n\
+            #OFFSET:-0.5;\n\
+            #BPMS:0=120;\n\
+            #NOTEDATA:;\n\
+            #CHARTNAME:Mild;\n\
+            #STEPSTYPE:dance-single;\n\
+            #DESCRIPTION:;\n\
+            #DIFFICULTY:Easy;\n\
+            #METER:3;\n\
+            #RADARVALUES:0.1,0.2,0.3,0.4,0.5;\n\
+            #BPMS:0=240;\n\
+            #NOTES:\n1000\n0100\n0010\n0001\n;\n\
+            #NOTEDATA:;\n\
+            #STEPSTYPE:dance-double;\n\
+            #DESCRIPTION:someone;\n\
+            #DIFFICULTY:Challenge;\n\
+            #METER:12;\n\
+            #NOTES:\n10000001\n,\n00011000\n;\n";
+
+        let chart = SMChart::from_sm(Cursor::new(ssc_to_sm(simfile))).unwrap();
+        assert_eq!(chart.title, "Test");
+        assert_eq!(chart.offset, -0.5);
+        assert_eq!(chart.bpms.len(), 1);
+        assert_eq!(chart.bpms[0].bpm, 120.0);
+
+        assert_eq!(chart.notes.len(), 2);
+        assert_eq!(chart.notes[0].style, "dance-single");
+        assert_eq!(chart.notes[0].description, "Mild");
+        assert_eq!(chart.notes[0].difficulty, "Easy");
+        assert_eq!(chart.notes[0].chart_meter, 3);
+        assert_eq!(chart.notes[0].measures.len(), 1);
+        assert_eq!(chart.notes[1].style, "dance-double");
+        assert_eq!(chart.notes[1].description, "someone");
+        assert_eq!(chart.notes[1].measures.len(), 2);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::Error;
+use crate::feet::CompactPlacement;
 use ordered_float::NotNan;
 use std::ops::{Add, Div};
 
@@ -6,7 +8,18 @@ pub struct StagePosition(pub NotNan<f64>, pub NotNan<f64>);
 
 impl StagePosition {
     pub fn new(x: f64, y: f64) -> StagePosition {
-        StagePosition(NotNan::new(x).unwrap(), NotNan::new(y).unwrap())
+        Self::try_new(x, y).unwrap()
+    }
+
+    pub fn try_new(x: f64, y: f64) -> Result<StagePosition, Error> {
+        let x = NotNan::new(x).map_err(|_| Error::NanPosition)?;
+        let y = NotNan::new(y).map_err(|_| Error::NanPosition)?;
+        Ok(StagePosition(x, y))
+    }
+
+    /// Divide both coordinates by `rhs`, returning an error if one of them becomes NaN (e.g `0 / 0`).
+    pub fn checked_div(self, rhs: f64) -> Result<StagePosition, Error> {
+        Self::try_new(*self.0 / rhs, *self.1 / rhs)
     }
 }
 
@@ -21,11 +34,9 @@ impl Add for StagePosition {
 impl Div<f64> for StagePosition {
     type Output = Self;
 
+    /// Panics if a coordinate becomes NaN, see [`StagePosition::checked_div`].
     fn div(self, rhs: f64) -> Self::Output {
-        StagePosition(
-            NotNan::new(self.0 / rhs).unwrap(),
-            NotNan::new(self.1 / rhs).unwrap(),
-        )
+        self.checked_div(rhs).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -35,6 +46,30 @@ pub struct DanceStage {
 }
 
 impl DanceStage {
+    /// Create a stage with a custom layout, with one position per column.
+    ///
+    /// Panics if two columns have the same position or if there are too many columns, see [`DanceStage::try_new`].
+    pub fn new(columns: Vec<StagePosition>) -> Self {
+        Self::try_new(columns).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Same as [`DanceStage::new`], but returns an error if two columns have the same position,
+    /// or if there are too many columns.
+    pub fn try_new(columns: Vec<StagePosition>) -> Result<Self, Error> {
+        if columns.len() > CompactPlacement::MAX_COLUMNS {
+            return Err(Error::TooManyColumns {
+                max: CompactPlacement::MAX_COLUMNS,
+                actual: columns.len(),
+            });
+        }
+        for (second, position) in columns.iter().enumerate() {
+            if let Some(first) = columns[..second].iter().position(|p| p == position) {
+                return Err(Error::DuplicatePosition { first, second });
+            }
+        }
+        Ok(DanceStage { columns })
+    }
+
     pub fn ddr_solo() -> Self {
         DanceStage {
             columns: vec![
@@ -46,6 +81,46 @@ impl DanceStage {
         }
     }
 
+    /// Two DDR solo stages next to each other.
+    pub fn ddr_double() -> Self {
+        DanceStage {
+            columns: vec![
+                StagePosition::new(-2.5, 0.0),
+                StagePosition::new(-1.5, -1.0),
+                StagePosition::new(-1.5, 1.0),
+                StagePosition::new(-0.5, 0.0),
+                StagePosition::new(0.5, 0.0),
+                StagePosition::new(1.5, -1.0),
+                StagePosition::new(1.5, 1.0),
+                StagePosition::new(2.5, 0.0),
+            ],
+        }
+    }
+
+    pub fn pump_single() -> Self {
+        DanceStage {
+            columns: vec![
+                StagePosition::new(-1.0, -1.0),
+                StagePosition::new(-1.0, 1.0),
+                StagePosition::new(0.0, 0.0),
+                StagePosition::new(1.0, 1.0),
+                StagePosition::new(1.0, -1.0),
+            ],
+        }
+    }
+
+    /// Get the stage used by a chart style (e.g `dance-single`).
+    ///
+    /// Styles are compared case-insensitively, like StepMania does.
+    pub fn for_style(style: &str) -> Option<Self> {
+        match style {
+            s if s.eq_ignore_ascii_case("dance-single") => Some(Self::ddr_solo()),
+            s if s.eq_ignore_ascii_case("dance-double") => Some(Self::ddr_double()),
+            s if s.eq_ignore_ascii_case("pump-single") => Some(Self::pump_single()),
+            _ => None,
+        }
+    }
+
     pub fn column_count(&self) -> usize {
         self.columns.len()
     }
@@ -54,6 +129,12 @@ impl DanceStage {
         self.distance_between(a, b) < 2.
     }
 
+    /// Whether the panel is on the outer edge of the stage, level with its center.
+    ///
+    /// Switching feet on a side panel means crossing over, which is why sideswitches cost more
+    /// than footswitches. The P1 right and P2 left panels of [`DanceStage::ddr_double`] are in the
+    /// middle of the stage, where switching feet is as easy as on the center panels of a single pad,
+    /// so they aren't side panels.
     pub fn is_side_panel(&self, p: usize) -> bool {
         self.y(p) == 0.0 && self.x(p).abs() >= 1.0
     }
@@ -133,6 +214,39 @@ mod tests {
         assert_ne!(stage.is_valid_bracket(1, 2), true);
     }
 
+    #[test]
+    fn test_stage_presets() {
+        assert_eq!(
+            DanceStage::for_style("dance-single")
+                .unwrap()
+                .column_count(),
+            4
+        );
+        assert_eq!(
+            DanceStage::for_style("dance-double")
+                .unwrap()
+                .column_count(),
+            8
+        );
+        assert_eq!(
+            DanceStage::for_style("pump-single").unwrap().column_count(),
+            5
+        );
+        assert_eq!(DanceStage::for_style("kb7-single"), None);
+        assert_eq!(
+            DanceStage::for_style("Dance-Single"),
+            Some(DanceStage::ddr_solo())
+        );
+
+        // Only the outer panels of each pad are side panels, the middle ones are between the feet
+        let stage = DanceStage::ddr_double();
+        assert!(stage.is_valid_bracket(3, 4));
+        let side_panels = (0..stage.column_count())
+            .filter(|&column| stage.is_side_panel(column))
+            .collect::<Vec<_>>();
+        assert_eq!(side_panels, [0, 7]);
+    }
+
     #[test]
     fn test_xy_difference() {
         let stage = DanceStage::ddr_solo();
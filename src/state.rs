@@ -1,26 +1,26 @@
-use crate::feet::{FootPart, FootPlacement, Side};
+use crate::feet::{CompactPlacement, FootPart, Side};
 use std::fmt::Display;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct State {
     // modified_columns: FootPlacement,
-    pub activated_columns: FootPlacement,
-    pub final_columns: FootPlacement,
+    pub activated_columns: CompactPlacement,
+    pub final_columns: CompactPlacement,
 }
 
 impl State {
     pub fn new(column_count: usize) -> State {
         State {
-            // modified_columns: FootPlacement::new(column_count),
-            activated_columns: FootPlacement::new(column_count),
-            final_columns: FootPlacement::new(column_count),
+            // modified_columns: CompactPlacement::new(column_count),
+            activated_columns: CompactPlacement::new(column_count),
+            final_columns: CompactPlacement::new(column_count),
         }
     }
 
-    pub fn append(&self, columns: &FootPlacement) -> State {
-        let column_count = columns.0.len();
-        debug_assert_eq!(column_count, self.activated_columns.0.len());
-        debug_assert_eq!(column_count, self.final_columns.0.len());
+    pub fn append(&self, columns: &CompactPlacement) -> State {
+        let column_count = columns.len();
+        debug_assert_eq!(column_count, self.activated_columns.len());
+        debug_assert_eq!(column_count, self.final_columns.len());
 
         let mut result = State::new(column_count);
 
@@ -28,7 +28,7 @@ impl State {
         for column in 0..column_count {
             let column_value = columns.at(column);
             if column_value != FootPart::None {
-                *result.activated_columns.at_mut(column) = column_value;
+                result.activated_columns.set(column, column_value);
 
                 // moved_part.insert(column_value);
             }
@@ -36,7 +36,9 @@ impl State {
 
         for column in 0..column_count {
             if result.activated_columns.at(column) != FootPart::None {
-                *result.final_columns.at_mut(column) = result.activated_columns.at(column);
+                result
+                    .final_columns
+                    .set(column, result.activated_columns.at(column));
                 continue;
             };
 
@@ -52,7 +54,7 @@ impl State {
                 // In case it's a bracket to tap transition,
                 // we should not keep the toe state.
                 if !bracket_to_tap_transition {
-                    *result.final_columns.at_mut(column) = prev_foot_part;
+                    result.final_columns.set(column, prev_foot_part);
                 }
             }
         }
@@ -73,7 +75,7 @@ impl State {
 impl Display for State {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "State ")?;
-        <FootPlacement as Display>::fmt(&self.final_columns, f)
+        <CompactPlacement as Display>::fmt(&self.final_columns, f)
     }
 }
 
@@ -84,7 +86,7 @@ mod tests {
     #[test]
     fn test_state_progression() {
         let state = State::new(4);
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::LeftHeel,
             FootPart::None,
             FootPart::None,
@@ -92,14 +94,14 @@ mod tests {
         ));
         assert_eq!(
             state.final_columns,
-            FootPlacement::from_ddr_solo(
+            CompactPlacement::from_ddr_solo(
                 FootPart::LeftHeel,
                 FootPart::None,
                 FootPart::None,
                 FootPart::None,
             )
         );
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::None,
             FootPart::None,
             FootPart::None,
@@ -107,14 +109,14 @@ mod tests {
         ));
         assert_eq!(
             state.final_columns,
-            FootPlacement::from_ddr_solo(
+            CompactPlacement::from_ddr_solo(
                 FootPart::LeftHeel,
                 FootPart::None,
                 FootPart::None,
                 FootPart::RightHeel,
             )
         );
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::None,
             FootPart::LeftHeel,
             FootPart::None,
@@ -122,7 +124,7 @@ mod tests {
         ));
         assert_eq!(
             state.final_columns,
-            FootPlacement::from_ddr_solo(
+            CompactPlacement::from_ddr_solo(
                 FootPart::None,
                 FootPart::LeftHeel,
                 FootPart::None,
@@ -131,7 +133,7 @@ mod tests {
         );
         assert_eq!(
             state.activated_columns,
-            FootPlacement::from_ddr_solo(
+            CompactPlacement::from_ddr_solo(
                 FootPart::None,
                 FootPart::LeftHeel,
                 FootPart::None,
@@ -143,7 +145,7 @@ mod tests {
     #[test]
     fn test_state_progression_brackets() {
         let state = State::new(4);
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::LeftHeel,
             FootPart::None,
             FootPart::LeftToe,
@@ -151,14 +153,14 @@ mod tests {
         ));
         assert_eq!(
             state.final_columns,
-            FootPlacement::from_ddr_solo(
+            CompactPlacement::from_ddr_solo(
                 FootPart::LeftHeel,
                 FootPart::None,
                 FootPart::LeftToe,
                 FootPart::None,
             )
         );
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::None,
             FootPart::None,
             FootPart::None,
@@ -166,14 +168,14 @@ mod tests {
         ));
         assert_eq!(
             state.final_columns,
-            FootPlacement::from_ddr_solo(
+            CompactPlacement::from_ddr_solo(
                 FootPart::LeftHeel,
                 FootPart::None,
                 FootPart::LeftToe,
                 FootPart::RightHeel,
             )
         );
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::None,
             FootPart::LeftHeel,
             FootPart::None,
@@ -181,7 +183,7 @@ mod tests {
         ));
         assert_eq!(
             state.final_columns,
-            FootPlacement::from_ddr_solo(
+            CompactPlacement::from_ddr_solo(
                 FootPart::None,
                 FootPart::LeftHeel,
                 FootPart::None,
@@ -193,13 +195,13 @@ mod tests {
     #[test]
     fn test_state_progression_bracket_consecutive() {
         let state = State::new(4);
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::LeftHeel,
             FootPart::None,
             FootPart::LeftToe,
             FootPart::None,
         ));
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::LeftToe,
             FootPart::LeftHeel,
             FootPart::None,
@@ -207,7 +209,7 @@ mod tests {
         ));
         assert_eq!(
             state.final_columns,
-            FootPlacement::from_ddr_solo(
+            CompactPlacement::from_ddr_solo(
                 FootPart::LeftToe,
                 FootPart::LeftHeel,
                 FootPart::None,
@@ -219,13 +221,13 @@ mod tests {
     #[test]
     fn test_state_progression_toe_to_other_heel() {
         let state = State::new(4);
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::LeftHeel,
             FootPart::None,
             FootPart::None,
             FootPart::None,
         ));
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::RightHeel,
             FootPart::RightToe,
             FootPart::None,
@@ -233,7 +235,7 @@ mod tests {
         ));
         assert_eq!(
             state.final_columns,
-            FootPlacement::from_ddr_solo(
+            CompactPlacement::from_ddr_solo(
                 FootPart::RightHeel,
                 FootPart::RightToe,
                 FootPart::None,
@@ -245,19 +247,19 @@ mod tests {
     #[test]
     fn test_state_progression_jack() {
         let state = State::new(4);
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::LeftHeel,
             FootPart::None,
             FootPart::None,
             FootPart::None,
         ));
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::None,
             FootPart::None,
             FootPart::None,
             FootPart::RightHeel,
         ));
-        let state = state.append(&FootPlacement::from_ddr_solo(
+        let state = state.append(&CompactPlacement::from_ddr_solo(
             FootPart::None,
             FootPart::None,
             FootPart::None,
@@ -265,7 +267,7 @@ mod tests {
         ));
         assert_eq!(
             state.final_columns,
-            FootPlacement::from_ddr_solo(
+            CompactPlacement::from_ddr_solo(
                 FootPart::LeftHeel,
                 FootPart::None,
                 FootPart::None,
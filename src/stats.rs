@@ -0,0 +1,191 @@
+use crate::feet::{FootPart, Side};
+use crate::graph::StepEntry;
+use crate::stage::DanceStage;
+use crate::tech::Tech;
+use std::collections::BTreeMap;
+
+/// Window used to find the peak notes per second.
+const PEAK_NPS_WINDOW: f64 = 1.0;
+/// Maximum time between two steps for them to be considered part of the same stream.
+pub(crate) const STREAM_THRESHOLD: f64 = 0.2;
+
+/// Summary of a whole chart, computed from its predicted steps.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChartStats {
+    /// Time between the first and the last row, in seconds.
+    pub duration: f64,
+    /// Amount of rows where at least one foot stepped.
+    pub step_count: usize,
+    /// Amount of notes stepped on (a jump counts as two notes).
+    pub note_count: usize,
+    pub tech_counts: BTreeMap<Tech, usize>,
+    pub average_nps: f64,
+    pub peak_nps: f64,
+    /// Amount of steps in the longest stream.
+    pub longest_stream: usize,
+    pub left_steps: usize,
+    pub right_steps: usize,
+    /// Total distance traveled by both feet, in stage units.
+    pub travel_distance: f64,
+}
+
+impl ChartStats {
+    pub fn new(stage: &DanceStage, steps: &[StepEntry]) -> Self {
+        let duration = match (steps.first(), steps.last()) {
+            (Some(first), Some(last)) => last.time.0 - first.time.0,
+            _ => 0.0,
+        };
+
+        let mut stats = ChartStats {
+            duration,
+            step_count: 0,
+            note_count: 0,
+            tech_counts: BTreeMap::new(),
+            average_nps: 0.0,
+            peak_nps: 0.0,
+            longest_stream: 0,
+            left_steps: 0,
+            right_steps: 0,
+            travel_distance: 0.0,
+        };
+
+        let stepping = steps
+            .iter()
+            .filter(|step| step.activated_columns.part_count() != 0)
+            .collect::<Vec<_>>();
+
+        let mut stream = 0;
+        for (i, step) in stepping.iter().enumerate() {
+            stats.step_count += 1;
+            stats.note_count += step.activated_columns.part_count();
+
+            for tech in step.tech.iter() {
+                *stats.tech_counts.entry(tech).or_default() += 1;
+            }
+
+            let sides = [Side::Left, Side::Right].map(|side| {
+                step.activated_columns.contains(FootPart::heel(side))
+                    || step.activated_columns.contains(FootPart::toe(side))
+            });
+            stats.left_steps += sides[0] as usize;
+            stats.right_steps += sides[1] as usize;
+
+            let Some(prev) = i.checked_sub(1).map(|i| stepping[i]) else {
+                stream = 1;
+                stats.longest_stream = 1;
+                continue;
+            };
+
+            if step.time.0 - prev.time.0 <= STREAM_THRESHOLD {
+                stream += 1;
+            } else {
+                stream = 1;
+            }
+            stats.longest_stream = stats.longest_stream.max(stream);
+
+            stats.travel_distance += travel_distance(stage, prev, step);
+        }
+
+        if duration > 0.0 {
+            stats.average_nps = stats.note_count as f64 / duration;
+        }
+        stats.peak_nps = peak_nps(&stepping);
+
+        stats
+    }
+
+    pub fn tech_count(&self, tech: Tech) -> usize {
+        self.tech_counts.get(&tech).copied().unwrap_or(0)
+    }
+
+    /// Amount of times the tech is performed per minute of chart.
+    pub fn tech_per_minute(&self, tech: Tech) -> f64 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        self.tech_count(tech) as f64 / (self.duration / 60.0)
+    }
+}
+
+fn travel_distance(stage: &DanceStage, prev: &StepEntry, next: &StepEntry) -> f64 {
+    let mut distance = 0.0;
+    for part in FootPart::all_except_none() {
+        if !next.activated_columns.contains(part) {
+            continue;
+        }
+
+        let Some(prev_position) = prev.columns.get_foot_part_index(part) else {
+            continue;
+        };
+        let Some(next_position) = next.columns.get_foot_part_index(part) else {
+            continue;
+        };
+
+        // Same as the movement cost, tap -> bracket transitions only count the heel movement.
+        if next.columns.get_foot_part_index(part.other_part()) == Some(prev_position) {
+            continue;
+        }
+
+        distance += stage.distance_between(prev_position, next_position);
+    }
+    distance
+}
+
+fn peak_nps(steps: &[&StepEntry]) -> f64 {
+    let mut peak = 0;
+    let mut window_notes = 0;
+    let mut window_start = 0;
+    for step in steps {
+        window_notes += step.activated_columns.part_count();
+        while step.time.0 - steps[window_start].time.0 >= PEAK_NPS_WINDOW {
+            window_notes -= steps[window_start].activated_columns.part_count();
+            window_start += 1;
+        }
+        peak = peak.max(window_notes);
+    }
+
+    peak as f64 / PEAK_NPS_WINDOW
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StepGraph;
+    use approx::assert_relative_eq;
+    use danceparser::{NoteKind, Row};
+
+    #[test]
+    fn test_stream_stats() {
+        let stage = DanceStage::ddr_solo();
+        let mut graph = StepGraph::new(stage.clone());
+        for (i, column) in [0, 1, 2, 3, 0, 1, 2, 3].into_iter().enumerate() {
+            let mut columns = vec![NoteKind::Empty; 4];
+            columns[column] = NoteKind::Tap;
+            graph.append(i as f64 * 0.125, &Row { columns });
+        }
+        graph.append(
+            3.0,
+            &Row {
+                columns: vec![
+                    NoteKind::Tap,
+                    NoteKind::Empty,
+                    NoteKind::Empty,
+                    NoteKind::Tap,
+                ],
+            },
+        );
+
+        let stats = ChartStats::new(&stage, &graph.compute_steps());
+        assert_eq!(stats.step_count, 9);
+        assert_eq!(stats.note_count, 10);
+        assert_eq!(stats.longest_stream, 8);
+        assert_eq!(stats.left_steps, 5);
+        assert_eq!(stats.right_steps, 5);
+        assert_eq!(stats.tech_count(Tech::Jump), 1);
+        assert_relative_eq!(stats.peak_nps, 8.0);
+        assert_relative_eq!(stats.average_nps, 10.0 / 3.0);
+        assert_relative_eq!(stats.tech_per_minute(Tech::Jump), 20.0);
+        assert!(stats.travel_distance > 0.0);
+    }
+}
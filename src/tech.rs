@@ -0,0 +1,402 @@
+use crate::feet::{FootPart, Side};
+use crate::stage::DanceStage;
+use crate::state::State;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Kind of move performed by a step.
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Tech {
+    /// Both feet hit the stage at the same time.
+    Jump,
+    /// One foot hits two columns using both the heel and the toe.
+    Bracket,
+    /// A foot hits the column it was already standing on.
+    Jack,
+    /// A foot hits a center column the other foot was standing on.
+    Footswitch,
+    /// A foot hits a side column the other foot was standing on.
+    Sideswitch,
+    /// The feet are crossed, with the crossing foot in front of the other one.
+    FrontCrossover,
+    /// The feet are crossed, with the crossing foot behind the other one.
+    BackCrossover,
+    /// The same foot steps twice in a row on different columns.
+    Doublestep,
+    /// A foot moves straight across the center (e.g `Up` to `Down`).
+    Candle,
+    /// The body turns around to face backwards.
+    Spin,
+    /// A step taken while a hold is being held on another column.
+    HoldStep,
+}
+
+impl Tech {
+    pub const ALL: [Tech; 11] = [
+        Tech::Jump,
+        Tech::Bracket,
+        Tech::Jack,
+        Tech::Footswitch,
+        Tech::Sideswitch,
+        Tech::FrontCrossover,
+        Tech::BackCrossover,
+        Tech::Doublestep,
+        Tech::Candle,
+        Tech::Spin,
+        Tech::HoldStep,
+    ];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Tech::Jump => "jump",
+            Tech::Bracket => "bracket",
+            Tech::Jack => "jack",
+            Tech::Footswitch => "footswitch",
+            Tech::Sideswitch => "sideswitch",
+            Tech::FrontCrossover => "front-crossover",
+            Tech::BackCrossover => "back-crossover",
+            Tech::Doublestep => "doublestep",
+            Tech::Candle => "candle",
+            Tech::Spin => "spin",
+            Tech::HoldStep => "hold-step",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|tech| tech.name() == s)
+    }
+
+    const fn bit(&self) -> u16 {
+        1 << *self as u16
+    }
+}
+
+impl Display for Tech {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Set of [`Tech`] performed by a step.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TechTags(u16);
+
+impl TechTags {
+    pub const fn empty() -> Self {
+        TechTags(0)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, tech: Tech) -> bool {
+        self.0 & tech.bit() != 0
+    }
+
+    pub fn insert(&mut self, tech: Tech) {
+        self.0 |= tech.bit();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Tech> + '_ {
+        Tech::ALL.into_iter().filter(|tech| self.contains(*tech))
+    }
+}
+
+impl FromIterator<Tech> for TechTags {
+    fn from_iter<T: IntoIterator<Item = Tech>>(iter: T) -> Self {
+        let mut tags = TechTags::empty();
+        for tech in iter {
+            tags.insert(tech);
+        }
+        tags
+    }
+}
+
+impl Debug for TechTags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl Display for TechTags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, tech) in self.iter().enumerate() {
+            if i != 0 {
+                write!(f, "|")?;
+            }
+            write!(f, "{tech}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for TechTags {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TechTags {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Vec::<Tech>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+/// Get the average (x, y) position of a foot, considering both the heel and the toe.
+fn foot_position(stage: &DanceStage, state: &State, side: Side) -> Option<(f64, f64)> {
+    let heel = state
+        .final_columns
+        .get_foot_part_index(FootPart::heel(side))?;
+    let toe = state
+        .final_columns
+        .get_foot_part_index(FootPart::toe(side))
+        .unwrap_or(heel);
+
+    let position = stage.average_position(heel, toe);
+    Some((position.0.into_inner(), position.1.into_inner()))
+}
+
+fn is_jump(state: &State) -> bool {
+    state.side_activated(Side::Left) && state.side_activated(Side::Right)
+}
+
+fn is_facing_backwards(stage: &DanceStage, state: &State) -> bool {
+    let left = state.final_columns.get_foot_part_index(FootPart::LeftHeel);
+    let right = state.final_columns.get_foot_part_index(FootPart::RightHeel);
+    match (left, right) {
+        (Some(left), Some(right)) => stage.x_difference(left, right) <= -1.0 + f64::EPSILON,
+        _ => false,
+    }
+}
+
+/// Classify the step going from `prev` to `next`.
+///
+/// `prev` should be the last state which stepped on the stage,
+/// and `holding` whether a hold is held on a column which isn't stepped on by `next`.
+pub(crate) fn classify(stage: &DanceStage, prev: &State, next: &State, holding: bool) -> TechTags {
+    let mut tags = TechTags::empty();
+
+    let left = next.side_activated(Side::Left);
+    let right = next.side_activated(Side::Right);
+    if !left && !right {
+        return tags;
+    }
+
+    if left && right {
+        tags.insert(Tech::Jump);
+    }
+
+    if next.activated_columns.is_bracketing(Side::Left)
+        || next.activated_columns.is_bracketing(Side::Right)
+    {
+        tags.insert(Tech::Bracket);
+    }
+
+    if holding {
+        tags.insert(Tech::HoldStep);
+    }
+
+    for column in 0..stage.column_count() {
+        let part = next.activated_columns.at(column);
+        let prev_part = prev.final_columns.at(column);
+        if part == FootPart::None || prev_part == FootPart::None {
+            continue;
+        }
+
+        if prev_part.side() == part.side() {
+            tags.insert(Tech::Jack);
+        } else if stage.is_side_panel(column) {
+            tags.insert(Tech::Sideswitch);
+        } else {
+            tags.insert(Tech::Footswitch);
+        }
+    }
+
+    let moving_side = if left { Side::Left } else { Side::Right };
+
+    if !is_jump(next) && !is_jump(prev) && prev.side_activated(moving_side) {
+        let prev_heel = prev
+            .activated_columns
+            .get_foot_part_index(FootPart::heel(moving_side));
+        let next_heel = next
+            .activated_columns
+            .get_foot_part_index(FootPart::heel(moving_side));
+        let prev_toe = prev
+            .activated_columns
+            .get_foot_part_index(FootPart::toe(moving_side));
+        let next_toe = next
+            .activated_columns
+            .get_foot_part_index(FootPart::toe(moving_side));
+        if prev_heel != next_heel || prev_toe != next_toe {
+            tags.insert(Tech::Doublestep);
+        }
+    }
+
+    if !is_jump(next) {
+        let prev_heel = prev
+            .final_columns
+            .get_foot_part_index(FootPart::heel(moving_side));
+        let next_heel = next
+            .final_columns
+            .get_foot_part_index(FootPart::heel(moving_side));
+        if let (Some(a), Some(b)) = (prev_heel, next_heel)
+            && stage.x(a) == stage.x(b)
+            && (stage.y(a) - stage.y(b)).abs() >= 2.0
+        {
+            tags.insert(Tech::Candle);
+        }
+    }
+
+    // Turning around also crosses the feet, but it's only counted as a spin
+    if is_facing_backwards(stage, next) && !is_facing_backwards(stage, prev) {
+        tags.insert(Tech::Spin);
+    } else if let (Some(left_position), Some(right_position)) = (
+        foot_position(stage, next, Side::Left),
+        foot_position(stage, next, Side::Right),
+    ) && right_position.0 < left_position.0
+    {
+        // The crossing foot is the one furthest past the center of the stage,
+        // the front/back distinction depends on whether it's ahead of the other foot.
+        let left_crossing = match (left_position.0 + right_position.0).total_cmp(&0.0) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => moving_side == Side::Left,
+        };
+        let (crossing, other) = if left_crossing {
+            (left_position, right_position)
+        } else {
+            (right_position, left_position)
+        };
+
+        if crossing.1 >= other.1 {
+            tags.insert(Tech::FrontCrossover);
+        } else {
+            tags.insert(Tech::BackCrossover);
+        }
+    }
+
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feet::CompactPlacement;
+
+    fn step(state: &State, placement: &str) -> State {
+        state.append(&CompactPlacement::parse(placement))
+    }
+
+    #[test]
+    fn test_tags_display() {
+        let tags = [Tech::Jump, Tech::Bracket]
+            .into_iter()
+            .collect::<TechTags>();
+        assert_eq!(tags.to_string(), "jump|bracket");
+        assert_eq!(Tech::parse("hold-step"), Some(Tech::HoldStep));
+    }
+
+    #[test]
+    fn test_classify_basic() {
+        let stage = DanceStage::ddr_solo();
+        let start = State::new(4);
+        let a = step(&start, "L--R");
+        assert_eq!(
+            classify(&stage, &start, &a, false),
+            TechTags::from_iter([Tech::Jump])
+        );
+
+        let b = step(&a, "---R");
+        assert_eq!(
+            classify(&stage, &a, &b, false),
+            TechTags::from_iter([Tech::Jack])
+        );
+
+        let c = step(&b, "-R--");
+        assert_eq!(
+            classify(&stage, &b, &c, false),
+            TechTags::from_iter([Tech::Doublestep])
+        );
+
+        let d = step(&c, "-L--");
+        assert_eq!(
+            classify(&stage, &c, &d, true),
+            TechTags::from_iter([Tech::Footswitch, Tech::HoldStep])
+        );
+    }
+
+    #[test]
+    fn test_classify_crossover_candle() {
+        let stage = DanceStage::ddr_solo();
+        let start = State::new(4);
+        let a = step(&start, "L-R-");
+        let b = step(&a, "---L");
+        assert_eq!(
+            classify(&stage, &a, &b, false),
+            TechTags::from_iter([Tech::BackCrossover])
+        );
+
+        let a = step(&start, "LR--");
+        let b = step(&a, "---L");
+        assert_eq!(
+            classify(&stage, &a, &b, false),
+            TechTags::from_iter([Tech::FrontCrossover])
+        );
+
+        let c = step(&b, "--R-");
+        assert_eq!(
+            classify(&stage, &b, &c, false),
+            TechTags::from_iter([Tech::Candle, Tech::BackCrossover])
+        );
+
+        let d = step(&c, "R---");
+        assert_eq!(
+            classify(&stage, &c, &d, false),
+            TechTags::from_iter([Tech::Doublestep, Tech::Spin])
+        );
+    }
+
+    #[test]
+    fn test_classify_double_switches() {
+        let stage = DanceStage::ddr_double();
+        let start = State::new(8);
+
+        // Switching feet on the middle panels is a plain footswitch
+        let a = step(&start, "---L-R--");
+        let b = step(&a, "---R----");
+        assert!(classify(&stage, &a, &b, false).contains(Tech::Footswitch));
+        assert!(!classify(&stage, &a, &b, false).contains(Tech::Sideswitch));
+
+        // Switching on the outer panels is a sideswitch
+        let a = step(&start, "L--R----");
+        let b = step(&a, "R-------");
+        assert!(classify(&stage, &a, &b, false).contains(Tech::Sideswitch));
+        assert!(!classify(&stage, &a, &b, false).contains(Tech::Footswitch));
+    }
+
+    #[test]
+    fn test_classify_spin() {
+        let stage = DanceStage::ddr_solo();
+        let start = State::new(4);
+        let a = step(&step(&start, "LR--"), "---L");
+        let b = step(&a, "R---");
+        assert_eq!(
+            classify(&stage, &a, &b, false),
+            TechTags::from_iter([Tech::Spin])
+        );
+
+        // Turning back halfway is a crossover again
+        let c = step(&b, "--L-");
+        assert_eq!(
+            classify(&stage, &b, &c, false),
+            TechTags::from_iter([Tech::BackCrossover])
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use danceparser::SMChart;
+use danceparser::view::{NoteView, NoteViewer};
+use dancepredictor::{DanceStage, StepGraph};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::io::Cursor;
+
+/// Counts the allocations made by the current thread, so other tests running in parallel don't interfere.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn appended_rows_reuse_cached_permutations() {
+    let chart = SMChart::from_sm(Cursor::new(include_str!("../Eientewi Set 12A.sm")))
+        .expect("Failed to parse StepMania chart");
+    let rows = NoteViewer::new(&chart, &chart.notes[0]).collect::<Vec<_>>();
+    let (first_half, second_half) = rows.split_at(rows.len() / 2);
+
+    let mut graph = StepGraph::new(DanceStage::ddr_solo());
+    for &NoteView { time, row } in first_half {
+        graph.append(time, row);
+    }
+
+    // The permutations of the chart's press masks are cached by now, so appending a row
+    // only allocates its candidates, its transitions and a copy of the row
+    let before = allocations();
+    for &NoteView { time, row } in second_half {
+        graph.append(time, row);
+    }
+    let allocations = allocations() - before;
+    assert!(
+        allocations <= second_half.len() * 4,
+        "{allocations} allocations for {} rows",
+        second_half.len()
+    );
+}